@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use js_component_bindgen::{generate_types, source::wit_parser::Resolve, transpile};
+use js_component_bindgen::{
+    generate_types, source::wit_parser::Resolve, transpile, transpile_to, ComponentInfo,
+    DirectorySink,
+};
 
 /// Calls [`write!`] with the passed arguments and unwraps the result.
 ///
@@ -60,51 +63,42 @@ export!(JsComponentBindgenComponent);
 impl Guest for JsComponentBindgenComponent {
     fn generate(component: Vec<u8>, options: GenerateOptions) -> Result<Transpiled, String> {
         let component = wat::parse_bytes(&component).map_err(|e| format!("{e}"))?;
-        let opts = js_component_bindgen::TranspileOpts {
-            name: options.name,
-            no_typescript: options.no_typescript.unwrap_or(false),
-            instantiation: options.instantiation.map(Into::into),
-            map: options.map.map(|map| map.into_iter().collect()),
-            no_nodejs_compat: options.no_nodejs_compat.unwrap_or(false),
-            base64_cutoff: options.base64_cutoff.unwrap_or(5000) as usize,
-            tla_compat: options
-                .tla_compat
-                .unwrap_or(options.compat.unwrap_or(false)),
-            valid_lifting_optimization: options.valid_lifting_optimization.unwrap_or(false),
-            tracing: options.tracing.unwrap_or(false),
-            no_namespaced_exports: options.no_namespaced_exports.unwrap_or(false),
-            multi_memory: options.multi_memory.unwrap_or(false),
-            import_bindings: options.import_bindings.map(Into::into),
-        };
 
         let js_component_bindgen::Transpiled {
             files,
             imports,
-            mut exports,
-        } = transpile(&component, opts)
+            exports,
+        } = transpile(&component, transpile_opts(options))
             .map_err(|e| format!("{:?}", e))
             .map_err(|e| e.to_string())?;
 
         Ok(Transpiled {
             files,
             imports,
-            exports: exports
-                .drain(..)
-                .map(|(name, expt)| {
-                    (
-                        name,
-                        match expt {
-                            wasmtime_environ::component::Export::LiftedFunction { .. } => {
-                                ExportType::Function
-                            }
-                            wasmtime_environ::component::Export::Instance { .. } => {
-                                ExportType::Instance
-                            }
-                            _ => panic!("Unexpected export type"),
-                        },
-                    )
-                })
+            exports: export_types(exports),
+        })
+    }
+
+    fn generate_to(
+        component: Vec<u8>,
+        options: GenerateOptions,
+        out_dir: String,
+    ) -> Result<TranspiledTo, String> {
+        let component = wat::parse_bytes(&component).map_err(|e| format!("{e}"))?;
+
+        let mut sink = DirectorySink::new(out_dir);
+        let ComponentInfo { imports, exports } =
+            transpile_to(&component, transpile_opts(options), &mut sink)
+                .map_err(|e| format!("{:?}", e))?;
+
+        Ok(TranspiledTo {
+            files: sink
+                .written()
+                .iter()
+                .map(|(name, size)| (name.clone(), *size as u64))
                 .collect(),
+            imports,
+            exports: export_types(exports),
         })
     }
 
@@ -159,6 +153,21 @@ impl Guest for JsComponentBindgenComponent {
             tracing: false,
             no_namespaced_exports: false,
             multi_memory: false,
+            branded_resources: opts.branded_resources.unwrap_or(false),
+            stability_annotations: opts.stability_annotations.unwrap_or(false),
+            instantiate_sync: opts.instantiate_sync.unwrap_or(false),
+            errors_extend_error: opts.errors_extend_error.unwrap_or(false),
+            wrap_sync_in_promise: opts.wrap_sync_in_promise.unwrap_or(false),
+            variant_type_guards: opts.variant_type_guards.unwrap_or(false),
+            equality_helpers: opts.equality_helpers.unwrap_or(false),
+            variant_match_helpers: opts.variant_match_helpers.unwrap_or(false),
+            flatten_exports: opts.flatten_exports.unwrap_or(false),
+            runtime_validation: false,
+            nullable_options: opts.nullable_options.unwrap_or(false),
+            abort_signals: opts.abort_signals.unwrap_or(false),
+            enum_case_arrays: opts.enum_case_arrays.unwrap_or(false),
+            borrow_callbacks: opts.borrow_callbacks.unwrap_or(false),
+            manifest: false,
             import_bindings: None,
         };
 
@@ -167,3 +176,57 @@ impl Guest for JsComponentBindgenComponent {
         Ok(files)
     }
 }
+
+fn transpile_opts(options: GenerateOptions) -> js_component_bindgen::TranspileOpts {
+    js_component_bindgen::TranspileOpts {
+        name: options.name,
+        no_typescript: options.no_typescript.unwrap_or(false),
+        instantiation: options.instantiation.map(Into::into),
+        map: options.map.map(|map| map.into_iter().collect()),
+        no_nodejs_compat: options.no_nodejs_compat.unwrap_or(false),
+        base64_cutoff: options.base64_cutoff.unwrap_or(5000) as usize,
+        tla_compat: options
+            .tla_compat
+            .unwrap_or(options.compat.unwrap_or(false)),
+        valid_lifting_optimization: options.valid_lifting_optimization.unwrap_or(false),
+        tracing: options.tracing.unwrap_or(false),
+        no_namespaced_exports: options.no_namespaced_exports.unwrap_or(false),
+        multi_memory: options.multi_memory.unwrap_or(false),
+        branded_resources: options.branded_resources.unwrap_or(false),
+        stability_annotations: options.stability_annotations.unwrap_or(false),
+        instantiate_sync: options.instantiate_sync.unwrap_or(false),
+        errors_extend_error: options.errors_extend_error.unwrap_or(false),
+        wrap_sync_in_promise: options.wrap_sync_in_promise.unwrap_or(false),
+        variant_type_guards: options.variant_type_guards.unwrap_or(false),
+        equality_helpers: options.equality_helpers.unwrap_or(false),
+        variant_match_helpers: options.variant_match_helpers.unwrap_or(false),
+        flatten_exports: options.flatten_exports.unwrap_or(false),
+        runtime_validation: options.runtime_validation.unwrap_or(false),
+        nullable_options: options.nullable_options.unwrap_or(false),
+        abort_signals: options.abort_signals.unwrap_or(false),
+        enum_case_arrays: options.enum_case_arrays.unwrap_or(false),
+        borrow_callbacks: options.borrow_callbacks.unwrap_or(false),
+        manifest: options.manifest.unwrap_or(false),
+        import_bindings: options.import_bindings.map(Into::into),
+    }
+}
+
+fn export_types(
+    exports: Vec<(String, wasmtime_environ::component::Export)>,
+) -> Vec<(String, ExportType)> {
+    exports
+        .into_iter()
+        .map(|(name, expt)| {
+            (
+                name,
+                match expt {
+                    wasmtime_environ::component::Export::LiftedFunction { .. } => {
+                        ExportType::Function
+                    }
+                    wasmtime_environ::component::Export::Instance { .. } => ExportType::Instance,
+                    _ => panic!("Unexpected export type"),
+                },
+            )
+        })
+        .collect()
+}
@@ -1,11 +1,13 @@
-use heck::ToLowerCamelCase;
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
 
 use crate::intrinsics::Intrinsic;
 use crate::names::{maybe_quote_id, maybe_quote_member, LocalNames};
 use crate::source::Source;
 use crate::{uwrite, uwriteln, TranspileOpts};
+use anyhow::{bail, Result};
 use std::collections::BTreeMap;
 use std::fmt::Write;
+use wit_parser::{FunctionKind, InterfaceId, Resolve, TypeDefKind, WorldId, WorldItem};
 
 type LocalName = String;
 
@@ -151,6 +153,18 @@ impl EsmBindgen {
             }
             return;
         }
+        // with --flatten-exports the members of the (single) exported
+        // interface are exported in place of its namespace object
+        let flattened = if opts.flatten_exports {
+            self.exports
+                .iter()
+                .find_map(|(export_name, export)| match export {
+                    ExportBinding::Interface(iface) => Some((export_name.as_str(), iface)),
+                    ExportBinding::Local(_) => None,
+                })
+        } else {
+            None
+        };
         // first create all the interfaces
         // we currently only support first-level nesting so there is no ordering to figure out
         // in the process we also populate the alias info
@@ -158,6 +172,9 @@ impl EsmBindgen {
             let ExportBinding::Interface(iface) = export else {
                 continue;
             };
+            if flattened.is_some_and(|(flattened, _)| flattened == export_name) {
+                continue;
+            }
             let (local_name, _) =
                 local_names.get_or_create(&format!("export:{export_name}"), export_name);
             uwriteln!(output, "const {local_name} = {{");
@@ -179,6 +196,9 @@ impl EsmBindgen {
             if first {
                 first = false
             }
+            if flattened.is_some_and(|(flattened, _)| flattened == export_name) {
+                continue;
+            }
             let local_name = match &self.exports[export_name] {
                 ExportBinding::Local(local_name) => local_name,
                 ExportBinding::Interface(_) => local_names.get(&format!("export:{}", export_name)),
@@ -199,6 +219,23 @@ impl EsmBindgen {
             if first {
                 first = false
             }
+            if let Some((flattened, iface)) = flattened {
+                if flattened == export_name {
+                    for (member_name, member) in iface {
+                        let ExportBinding::Local(local_name) = member else {
+                            panic!("Unsupported nested export interface");
+                        };
+                        if local_name == member_name {
+                            uwrite!(output, "{local_name}, ");
+                        } else if instantiation {
+                            uwrite!(output, "{member_name}: {local_name}, ");
+                        } else {
+                            uwrite!(output, "{local_name} as {member_name}, ");
+                        }
+                    }
+                    continue;
+                }
+            }
             let local_name = match export {
                 ExportBinding::Local(local_name) => local_name,
                 ExportBinding::Interface(_) => local_names.get(&format!("export:{}", export_name)),
@@ -387,3 +424,43 @@ impl EsmBindgen {
         }
     }
 }
+
+/// The interface whose members are exported directly with `--flatten-exports`,
+/// which must be the only exported interface and have no members colliding
+/// with the exported functions
+pub(crate) fn flattened_export_interface(resolve: &Resolve, world: WorldId) -> Result<InterfaceId> {
+    let world = &resolve.worlds[world];
+    let mut ifaces = world.exports.values().filter_map(|item| match item {
+        WorldItem::Interface { id, .. } => Some(*id),
+        _ => None,
+    });
+    let (Some(id), None) = (ifaces.next(), ifaces.next()) else {
+        bail!("flattening exports requires the component to export exactly one interface");
+    };
+    let members = interface_export_members(resolve, id);
+    for (key, item) in &world.exports {
+        if let WorldItem::Function(_) = item {
+            let name = resolve.name_world_key(key).to_lower_camel_case();
+            if members.contains(&name) {
+                bail!("unable to flatten exports, the exported function `{name}` collides with a member of the exported interface");
+            }
+        }
+    }
+    Ok(id)
+}
+
+/// JS names of the resources and functions exported by an interface
+pub(crate) fn interface_export_members(resolve: &Resolve, id: InterfaceId) -> Vec<String> {
+    let iface = &resolve.interfaces[id];
+    let resources = iface
+        .types
+        .iter()
+        .filter(|(_, ty)| matches!(resolve.types[**ty].kind, TypeDefKind::Resource))
+        .map(|(name, _)| name.to_upper_camel_case());
+    let funcs = iface
+        .functions
+        .values()
+        .filter(|func| matches!(func.kind, FunctionKind::Freestanding))
+        .map(|func| func.name.to_lower_camel_case());
+    resources.chain(funcs).collect()
+}
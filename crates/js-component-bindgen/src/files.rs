@@ -1,11 +1,79 @@
+use anyhow::{Context, Result};
 use std::collections::btree_map::{BTreeMap, Entry};
+use std::fs;
+use std::path::PathBuf;
 
-#[derive(Default)]
-pub struct Files {
+/// Destination for the generated output files.
+///
+/// Files are written to the sink as soon as their contents are final, so that
+/// large outputs (such as core Wasm binaries) need not all be held in memory
+/// at once.
+pub trait OutputSink {
+    /// Called exactly once for every output file.
+    fn write_file(&mut self, name: &str, contents: &[u8]) -> Result<()>;
+}
+
+/// In-memory collection of the output files, in the order they were written.
+impl OutputSink for Vec<(String, Vec<u8>)> {
+    fn write_file(&mut self, name: &str, contents: &[u8]) -> Result<()> {
+        self.push((name.to_string(), contents.to_vec()));
+        Ok(())
+    }
+}
+
+/// Writes the output files into a directory, creating subdirectories as
+/// needed.
+pub struct DirectorySink {
+    dir: PathBuf,
+    written: Vec<(String, usize)>,
+}
+
+impl DirectorySink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DirectorySink {
+            dir: dir.into(),
+            written: Vec::new(),
+        }
+    }
+
+    /// The name and size of each file written so far, in the order they were
+    /// written.
+    pub fn written(&self) -> &[(String, usize)] {
+        &self.written
+    }
+}
+
+impl OutputSink for DirectorySink {
+    fn write_file(&mut self, name: &str, contents: &[u8]) -> Result<()> {
+        let path = self.dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, contents)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        self.written.push((name.to_string(), contents.len()));
+        Ok(())
+    }
+}
+
+/// Output files which are still being generated, in front of an [`OutputSink`].
+pub struct Files<'a> {
     files: BTreeMap<String, Vec<u8>>,
+    sink: &'a mut dyn OutputSink,
+    /// First error from the sink, reported by [`Files::finish`]
+    error: Option<anyhow::Error>,
 }
 
-impl Files {
+impl<'a> Files<'a> {
+    pub fn new(sink: &'a mut dyn OutputSink) -> Self {
+        Files {
+            files: BTreeMap::new(),
+            sink,
+            error: None,
+        }
+    }
+
     pub fn push(&mut self, name: &str, contents: &[u8]) {
         match self.files.entry(name.to_owned()) {
             Entry::Vacant(entry) => {
@@ -17,15 +85,89 @@ impl Files {
         }
     }
 
-    pub fn get_size(&mut self, name: &str) -> Option<usize> {
-        self.files.get(name).map(|data| data.len())
+    /// Hand a complete file over to the sink, without holding on to it first.
+    pub fn write(&mut self, name: &str, contents: &[u8]) {
+        if self.error.is_none() {
+            if let Err(e) = self.sink.write_file(name, contents) {
+                self.error = Some(e);
+            }
+        }
     }
 
-    pub fn remove(&mut self, name: &str) -> Option<Vec<u8>> {
-        self.files.remove(name)
+    /// Finalize all remaining files in name order.
+    pub fn finish(mut self) -> Result<()> {
+        for (name, contents) in std::mem::take(&mut self.files) {
+            if self.error.is_some() {
+                break;
+            }
+            if let Err(e) = self.sink.write_file(&name, &contents) {
+                self.error = Some(e);
+            }
+        }
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Files;
+
+    #[test]
+    fn write_order() {
+        let mut out = Vec::new();
+        let mut files = Files::new(&mut out);
+        files.push("b.wasm", b"b");
+        files.push("a.js", b"a");
+        files.write("c.wasm", b"c");
+        files.push("a.js", b"a");
+        files.finish().unwrap();
+        assert_eq!(
+            out,
+            [
+                ("c.wasm".to_string(), b"c".to_vec()),
+                ("a.js".to_string(), b"aa".to_vec()),
+                ("b.wasm".to_string(), b"b".to_vec()),
+            ]
+        );
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = (&'_ str, &'_ [u8])> {
-        self.files.iter().map(|p| (p.0.as_str(), p.1.as_slice()))
+    #[test]
+    fn directory_sink() {
+        let dir = std::env::temp_dir().join(format!("jco-directory-sink-{}", std::process::id()));
+        let mut sink = super::DirectorySink::new(&dir);
+        let mut files = Files::new(&mut sink);
+        files.push("interfaces/b.d.ts", b"b");
+        files.write("a.wasm", b"aa");
+        files.finish().unwrap();
+        assert_eq!(
+            sink.written(),
+            [
+                ("a.wasm".to_string(), 2),
+                ("interfaces/b.d.ts".to_string(), 1)
+            ]
+        );
+        assert_eq!(std::fs::read(dir.join("interfaces/b.d.ts")).unwrap(), b"b");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sink_error() {
+        struct Failing;
+        impl super::OutputSink for Failing {
+            fn write_file(&mut self, name: &str, _contents: &[u8]) -> anyhow::Result<()> {
+                anyhow::bail!("failed to write {name}")
+            }
+        }
+        let mut sink = Failing;
+        let mut files = Files::new(&mut sink);
+        files.push("b.js", b"b");
+        files.write("a.wasm", b"a");
+        assert_eq!(
+            files.finish().unwrap_err().to_string(),
+            "failed to write a.wasm"
+        );
     }
 }
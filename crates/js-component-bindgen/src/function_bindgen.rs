@@ -82,10 +82,30 @@ pub struct FunctionBindgen<'a> {
     pub realloc: Option<&'a String>,
     pub post_return: Option<&'a String>,
     pub tracing_prefix: Option<&'a String>,
+    /// The `module` and `function` fields of the trace events
+    pub tracing_target: Option<&'a String>,
+    /// Whether the lifted results are still to be passed to the trace handler
+    pub trace_return: bool,
+    /// Function named in the errors of the runtime validation of lowered
+    /// values, when enabled
+    pub validation_context: Option<&'a String>,
+    /// Whether option `none` values are lifted as `null` instead of
+    /// `undefined`
+    pub nullable_options: bool,
+    /// Whether the call is abandoned once the `options.signal` abort signal
+    /// argument is aborted
+    pub abort_signal: bool,
+    /// Whether borrows passed to the callee are only accessible through a
+    /// callback for the duration of the call
+    pub borrow_callbacks: bool,
     pub encoding: StringEncoding,
     pub callee: &'a str,
     pub callee_resource_dynamic: bool,
     pub resolve: &'a Resolve,
+    /// Variant types to lift into `Error` subclasses, with their class names
+    pub error_classes: &'a BTreeMap<TypeId, String>,
+    /// The error classes referenced by the generated code so far
+    pub used_error_classes: &'a mut BTreeSet<TypeId>,
 }
 
 impl FunctionBindgen<'_> {
@@ -149,6 +169,18 @@ impl FunctionBindgen<'_> {
         }
     }
 
+    /// With runtime validation, check that `op` is an integer in the range of
+    /// the WIT integer type `ty`
+    fn validate_int(&mut self, op: &str, ty: &str, min: &str, max: &str) -> String {
+        match self.validation_context {
+            Some(context) => {
+                let validate = self.intrinsic(Intrinsic::ValidateInt);
+                format!("{validate}({op}, {min}, {max}, '{ty}', '{context}')")
+            }
+            None => op.to_string(),
+        }
+    }
+
     fn bitcast(&mut self, cast: &Bitcast, op: &str) -> String {
         match cast {
             Bitcast::I32ToF32 => {
@@ -270,36 +302,49 @@ impl Bindgen for FunctionBindgen<'_> {
             // All values coming from the host and going to wasm need to have
             // their ranges validated, since the host could give us any value.
             Instruction::I32FromU8 => {
+                let op = self.validate_int(&operands[0], "u8", "0", "255");
                 let conv = self.intrinsic(Intrinsic::ToUint8);
-                results.push(format!("{conv}({op})", op = operands[0]))
+                results.push(format!("{conv}({op})"))
             }
             Instruction::I32FromS8 => {
+                let op = self.validate_int(&operands[0], "s8", "-128", "127");
                 let conv = self.intrinsic(Intrinsic::ToInt8);
-                results.push(format!("{conv}({op})", op = operands[0]))
+                results.push(format!("{conv}({op})"))
             }
             Instruction::I32FromU16 => {
+                let op = self.validate_int(&operands[0], "u16", "0", "65535");
                 let conv = self.intrinsic(Intrinsic::ToUint16);
-                results.push(format!("{conv}({op})", op = operands[0]))
+                results.push(format!("{conv}({op})"))
             }
             Instruction::I32FromS16 => {
+                let op = self.validate_int(&operands[0], "s16", "-32768", "32767");
                 let conv = self.intrinsic(Intrinsic::ToInt16);
-                results.push(format!("{conv}({op})", op = operands[0]))
+                results.push(format!("{conv}({op})"))
             }
             Instruction::I32FromU32 => {
+                let op = self.validate_int(&operands[0], "u32", "0", "4294967295");
                 let conv = self.intrinsic(Intrinsic::ToUint32);
-                results.push(format!("{conv}({op})", op = operands[0]))
+                results.push(format!("{conv}({op})"))
             }
             Instruction::I32FromS32 => {
+                let op = self.validate_int(&operands[0], "s32", "-2147483648", "2147483647");
                 let conv = self.intrinsic(Intrinsic::ToInt32);
-                results.push(format!("{conv}({op})", op = operands[0]))
+                results.push(format!("{conv}({op})"))
             }
             Instruction::I64FromU64 => {
+                let op = self.validate_int(&operands[0], "u64", "0n", "18446744073709551615n");
                 let conv = self.intrinsic(Intrinsic::ToBigUint64);
-                results.push(format!("{conv}({op})", op = operands[0]))
+                results.push(format!("{conv}({op})"))
             }
             Instruction::I64FromS64 => {
+                let op = self.validate_int(
+                    &operands[0],
+                    "s64",
+                    "-9223372036854775808n",
+                    "9223372036854775807n",
+                );
                 let conv = self.intrinsic(Intrinsic::ToBigInt64);
-                results.push(format!("{conv}({op})", op = operands[0]))
+                results.push(format!("{conv}({op})"))
             }
 
             // The native representation in JS of f32 and f64 is just a number,
@@ -350,10 +395,25 @@ impl Bindgen for FunctionBindgen<'_> {
                 results.push(format!("{} ? 1 : 0", operands[0]));
             }
 
-            Instruction::RecordLower { record, .. } => {
+            Instruction::RecordLower { record, name, .. } => {
                 // use destructuring field access to get each
                 // field individually.
                 let tmp = self.tmp();
+                let mut op = operands[0].clone();
+                if let Some(context) = self.validation_context {
+                    // optional fields may be left out
+                    let required = record
+                        .fields
+                        .iter()
+                        .filter(|field| as_nullable(self.resolve, &field.ty).is_none())
+                        .map(|field| format!("'{}'", field.name.to_lower_camel_case()))
+                        .collect::<Vec<_>>();
+                    let validate = self.intrinsic(Intrinsic::ValidateRecord);
+                    op = format!(
+                        "{validate}({op}, [{}], '{name}', '{context}')",
+                        required.join(", ")
+                    );
+                }
                 let mut expr = "var {".to_string();
                 for (i, field) in record.fields.iter().enumerate() {
                     if i > 0 {
@@ -365,7 +425,7 @@ impl Bindgen for FunctionBindgen<'_> {
                     expr.push_str(&name);
                     results.push(name);
                 }
-                uwrite!(self.src, "{} }} = {};\n", expr, operands[0]);
+                uwrite!(self.src, "{} }} = {};\n", expr, op);
             }
 
             Instruction::RecordLift { record, .. } => {
@@ -531,7 +591,7 @@ impl Bindgen for FunctionBindgen<'_> {
                 uwriteln!(self.src, "}}");
             }
 
-            Instruction::VariantLift { variant, name, .. } => {
+            Instruction::VariantLift { variant, name, ty } => {
                 let blocks = self
                     .blocks
                     .drain(self.blocks.len() - variant.cases.len()..)
@@ -546,10 +606,33 @@ impl Bindgen for FunctionBindgen<'_> {
                     switch ({op}) {{"
                 );
 
+                let error_class = self.error_classes.get(ty);
+                if error_class.is_some() {
+                    self.used_error_classes.insert(*ty);
+                }
+
                 for (i, (case, (block, block_results))) in
                     variant.cases.iter().zip(blocks).enumerate()
                 {
                     let tag = case.name.as_str();
+                    if let Some(error_class) = error_class {
+                        let val = if case.ty.is_some() {
+                            assert!(block_results.len() == 1);
+                            format!(", {}", block_results[0])
+                        } else {
+                            assert!(block_results.is_empty());
+                            String::new()
+                        };
+                        uwriteln!(
+                            self.src,
+                            "case {i}: {{
+                                {block}\
+                                variant{tmp} = new {error_class}('{tag}'{val});
+                                break;
+                            }}"
+                        );
+                        continue;
+                    }
                     uwriteln!(
                         self.src,
                         "case {i}: {{
@@ -656,6 +739,8 @@ impl Bindgen for FunctionBindgen<'_> {
                             }}"
                         ),
                     )
+                } else if self.nullable_options {
+                    ("null", some_result.into())
                 } else {
                     ("undefined", some_result.into())
                 };
@@ -838,10 +923,14 @@ impl Bindgen for FunctionBindgen<'_> {
                     }}"
                     );
                 }
+                let context = match self.validation_context {
+                    Some(context) => format!("{context}: "),
+                    None => String::new(),
+                };
                 uwriteln!(
                     self.src,
                     "
-                            throw new TypeError(`\"${{val{tmp}}}\" is not one of the cases of {name}`);
+                            throw new TypeError(`{context}\"${{val{tmp}}}\" is not one of the cases of {name}`);
                         }}
                     }}",
                 );
@@ -1052,9 +1141,12 @@ impl Bindgen for FunctionBindgen<'_> {
 
                 if let Some(prefix) = self.tracing_prefix {
                     let to_result_string = self.intrinsic(Intrinsic::ToResultString);
+                    let trace_handler = self.intrinsic(Intrinsic::TraceHandler);
+                    // the trace handler gets the lifted result on return
+                    self.trace_return = true;
                     uwriteln!(
                         self.src,
-                        "console.error(`{prefix} return {}`);",
+                        "if (!{trace_handler}) console.error(`{prefix} return {}`);",
                         if sig_results_length > 0 || !results.is_empty() {
                             format!("result=${{{to_result_string}(ret)}}")
                         } else {
@@ -1066,6 +1158,31 @@ impl Bindgen for FunctionBindgen<'_> {
 
             Instruction::CallInterface { func } => {
                 let results_length = func.results.len();
+                // borrow arguments, other than the method receiver, are
+                // passed as callbacks which stop working once the call returns
+                let mut operands = operands.to_vec();
+                let mut borrow_scopes = Vec::new();
+                if self.borrow_callbacks {
+                    let skip = if self.callee_resource_dynamic { 1 } else { 0 };
+                    for (i, (_, ty)) in func.params.iter().enumerate().skip(skip) {
+                        let Some(resource) = as_borrow(self.resolve, ty) else {
+                            continue;
+                        };
+                        let resource_name = self.resolve.types[resource]
+                            .name
+                            .as_ref()
+                            .unwrap()
+                            .to_upper_camel_case();
+                        let borrow_callback = self.intrinsic(Intrinsic::BorrowCallback);
+                        let scope = format!("borrowScope{}", self.tmp());
+                        uwriteln!(self.src, "const {scope} = {{ active: true }};");
+                        operands[i] = format!(
+                            "{borrow_callback}({}, {scope}, '{resource_name}')",
+                            operands[i]
+                        );
+                        borrow_scopes.push(scope);
+                    }
+                }
                 let call = if self.callee_resource_dynamic {
                     format!(
                         "{}.{}({})",
@@ -1079,39 +1196,62 @@ impl Bindgen for FunctionBindgen<'_> {
                 if self.err == ErrHandling::ResultCatchHandler {
                     // result<_, string> allows JS error coercion only, while
                     // any other result type will trap for arbitrary JS errors.
-                    let err_payload = if let (_, Some(Type::Id(err_ty))) =
-                        func.results.throws(self.resolve).unwrap()
-                    {
-                        match &self.resolve.types[*err_ty].kind {
-                            TypeDefKind::Type(Type::String) => {
-                                self.intrinsic(Intrinsic::GetErrorPayloadString)
-                            }
-                            _ => self.intrinsic(Intrinsic::GetErrorPayload),
+                    let err_ty = match func.results.throws(self.resolve).unwrap() {
+                        (_, Some(Type::Id(err_ty))) => Some(*err_ty),
+                        _ => None,
+                    };
+                    let err_payload = match err_ty.map(|ty| &self.resolve.types[ty].kind) {
+                        Some(TypeDefKind::Type(Type::String)) => {
+                            self.intrinsic(Intrinsic::GetErrorPayloadString)
                         }
-                    } else {
-                        self.intrinsic(Intrinsic::GetErrorPayload)
+                        _ => self.intrinsic(Intrinsic::GetErrorPayload),
                     };
+                    let mut err_val = format!("{err_payload}(e)");
+                    // instances of the error class are errors extending `Error`
+                    // that are still to be lowered as the variant
+                    if let Some(err_ty) = err_ty.map(|ty| crate::dealias(self.resolve, ty)) {
+                        if let Some(error_class) = self.error_classes.get(&err_ty) {
+                            self.used_error_classes.insert(err_ty);
+                            err_val = format!("e instanceof {error_class} ? e : {err_val}");
+                        }
+                    }
                     uwriteln!(
                         self.src,
                         "let ret;
                         try {{
                             ret = {{ tag: 'ok', val: {call} }};
                         }} catch (e) {{
-                            ret = {{ tag: 'err', val: {err_payload}(e) }};
-                        }}",
+                            ret = {{ tag: 'err', val: {err_val} }};
+                        {}",
+                        end_call_try(&borrow_scopes)
                     );
                     results.push("ret".to_string());
-                } else {
+                } else if borrow_scopes.is_empty() {
                     self.bind_results(results_length, results);
                     uwriteln!(self.src, "{call};");
+                } else {
+                    // the borrows are released even if the call throws
+                    if results_length == 1 {
+                        uwriteln!(self.src, "let ret;");
+                        uwrite!(self.src, "try {{\nret = ");
+                        results.push("ret".to_string());
+                    } else {
+                        uwriteln!(self.src, "try {{");
+                        self.bind_results(results_length, results);
+                    }
+                    uwriteln!(self.src, "{call};\n{}", end_call_try(&borrow_scopes));
                 }
 
-                if let Some(prefix) = self.tracing_prefix {
+                if let (Some(prefix), Some(target)) = (self.tracing_prefix, self.tracing_target) {
                     let to_result_string = self.intrinsic(Intrinsic::ToResultString);
+                    let trace_handler = self.intrinsic(Intrinsic::TraceHandler);
+                    let has_result = results_length > 0 || !results.is_empty();
                     uwriteln!(
                         self.src,
-                        "console.error(`{prefix} return {}`);",
-                        if results_length > 0 || !results.is_empty() {
+                        "if ({trace_handler}) {trace_handler}({{ type: 'return', {target}{} }});
+                        else console.error(`{prefix} return {}`);",
+                        if has_result { ", result: ret" } else { "" },
+                        if has_result {
                             format!("result=${{{to_result_string}(ret)}}")
                         } else {
                             "".to_string()
@@ -1152,17 +1292,45 @@ impl Bindgen for FunctionBindgen<'_> {
             }
 
             Instruction::Return { amt, .. } => {
+                let trace_return = match self.tracing_target {
+                    Some(target) if self.trace_return => {
+                        let trace_handler = self.intrinsic(Intrinsic::TraceHandler);
+                        Some(format!(
+                            "if ({trace_handler}) {trace_handler}({{ type: 'return', {target}{} }});",
+                            if *amt > 0 { ", result: retVal" } else { "" }
+                        ))
+                    }
+                    _ => None,
+                };
+                // the signal may have been aborted during the call
+                let abort_check = if self.abort_signal {
+                    Some("options?.signal?.throwIfAborted();")
+                } else {
+                    None
+                };
                 if *amt == 0 {
+                    if let Some(trace_return) = &trace_return {
+                        uwriteln!(self.src, "{trace_return}");
+                    }
                     if let Some(f) = &self.post_return {
                         uwriteln!(self.src, "{f}();");
                     }
+                    if let Some(abort_check) = abort_check {
+                        uwriteln!(self.src, "{abort_check}");
+                    }
                 } else if *amt == 1 && self.err == ErrHandling::ThrowResultErr {
                     let component_err = self.intrinsic(Intrinsic::ComponentError);
                     let op = &operands[0];
                     uwriteln!(self.src, "const retVal = {op};");
+                    if let Some(trace_return) = &trace_return {
+                        uwriteln!(self.src, "{trace_return}");
+                    }
                     if let Some(f) = &self.post_return {
                         uwriteln!(self.src, "{f}(ret);");
                     }
+                    if let Some(abort_check) = abort_check {
+                        uwriteln!(self.src, "{abort_check}");
+                    }
                     uwriteln!(
                         self.src,
                         "if (typeof retVal === 'object' && retVal.tag === 'err') {{
@@ -1171,22 +1339,26 @@ impl Bindgen for FunctionBindgen<'_> {
                         return retVal.val;"
                     );
                 } else {
-                    let ret_assign = if self.post_return.is_some() {
-                        "const retVal ="
-                    } else {
-                        "return"
-                    };
+                    let bind_ret = self.post_return.is_some()
+                        || trace_return.is_some()
+                        || abort_check.is_some();
+                    let ret_assign = if bind_ret { "const retVal =" } else { "return" };
                     if *amt == 1 {
                         uwriteln!(self.src, "{ret_assign} {};", operands[0]);
                     } else {
                         uwriteln!(self.src, "{ret_assign} [{}];", operands.join(", "));
                     }
+                    if let Some(trace_return) = &trace_return {
+                        uwriteln!(self.src, "{trace_return}");
+                    }
                     if let Some(f) = &self.post_return {
-                        uwriteln!(
-                            self.src,
-                            "{f}(ret);
-                            return retVal;"
-                        );
+                        uwriteln!(self.src, "{f}(ret);");
+                    }
+                    if let Some(abort_check) = abort_check {
+                        uwriteln!(self.src, "{abort_check}");
+                    }
+                    if bind_ret {
+                        uwriteln!(self.src, "return retVal;");
                     }
                 }
             }
@@ -1549,6 +1721,29 @@ pub fn maybe_null(resolve: &Resolve, ty: &Type) -> bool {
     as_nullable(resolve, ty).is_some()
 }
 
+/// The resource of a type that is a borrow handle, if any
+pub fn as_borrow(resolve: &Resolve, ty: &Type) -> Option<TypeId> {
+    let Type::Id(id) = ty else { return None };
+    match &resolve.types[crate::dealias(resolve, *id)].kind {
+        TypeDefKind::Handle(Handle::Borrow(resource)) => Some(crate::dealias(resolve, *resource)),
+        _ => None,
+    }
+}
+
+/// The end of the `try` block around a call, with a `finally` block stopping
+/// the borrow callbacks passed to it from working, if there are any
+fn end_call_try(borrow_scopes: &[String]) -> String {
+    if borrow_scopes.is_empty() {
+        return "}".to_string();
+    }
+    let mut src = "} finally {\n".to_string();
+    for scope in borrow_scopes {
+        uwriteln!(src, "{scope}.active = false;");
+    }
+    src.push('}');
+    src
+}
+
 pub fn array_ty(resolve: &Resolve, ty: &Type) -> Option<&'static str> {
     match ty {
         Type::Bool => None,
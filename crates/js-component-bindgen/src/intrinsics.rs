@@ -6,6 +6,8 @@ use std::fmt::Write;
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum Intrinsic {
     Base64Compile,
+    /// Callback-scoped access to a borrow passed to an import
+    BorrowCallback,
     ClampGuest,
     ComponentError,
     CurResourceBorrows,
@@ -62,6 +64,10 @@ pub enum Intrinsic {
     ToUint32,
     /// Implementation of https://tc39.es/ecma262/#sec-touint8.
     ToUint8,
+    /// Trace event arguments, with resources replaced by their handles
+    TraceArgs,
+    /// User callback receiving the trace events, if any
+    TraceHandler,
     Utf16Decoder,
     Utf16Encode,
     Utf8Decoder,
@@ -69,6 +75,10 @@ pub enum Intrinsic {
     Utf8EncodedLen,
     ValidateGuestChar,
     ValidateHostChar,
+    /// Runtime validation of a lowered integer against its WIT range
+    ValidateInt,
+    /// Runtime validation of a lowered record having its required fields
+    ValidateRecord,
 }
 
 /// Emits the intrinsic `i` to this file and then returns the name of the
@@ -89,6 +99,9 @@ pub fn render_intrinsics(
     if intrinsics.contains(&Intrinsic::Utf16Encode) {
         intrinsics.insert(Intrinsic::IsLE);
     }
+    if intrinsics.contains(&Intrinsic::TraceArgs) {
+        intrinsics.insert(Intrinsic::SymbolResourceHandle);
+    }
 
     if intrinsics.contains(&Intrinsic::F32ToI32) || intrinsics.contains(&Intrinsic::I32ToF32) {
         output.push_str(
@@ -124,6 +137,16 @@ pub fn render_intrinsics(
                 ")
             },
 
+            Intrinsic::BorrowCallback => output.push_str("
+                function borrowCallback(rsc, scope, name) {
+                    return function withBorrow(callback) {
+                        if (!scope.active) \
+                            throw new TypeError(`Resource error: borrowed \"${name}\" resource used after the call it was passed to returned`);
+                        return callback(rsc);
+                    };
+                }
+            "),
+
             Intrinsic::ClampGuest => output.push_str("
                 function clampGuest(i, min, max) {
                     if (i < min || i > max) \
@@ -618,6 +641,14 @@ pub fn render_intrinsics(
                         utf8EncodedLen = 0;
                         return 1;
                     }
+                    // ASCII encodes to one byte per code unit, so the size is known
+                    // upfront and it can be encoded directly into guest memory
+                    if (/^[\\x00-\\x7f]*$/.test(s)) {
+                        let ptr = realloc(0, 0, 1, s.length);
+                        utf8Encoder.encodeInto(s, new Uint8Array(memory.buffer, ptr, s.length));
+                        utf8EncodedLen = s.length;
+                        return ptr;
+                    }
                     let buf = utf8Encoder.encode(s);
                     let ptr = realloc(0, 0, 1, buf.length);
                     new Uint8Array(memory.buffer).set(buf, ptr);
@@ -644,6 +675,44 @@ pub fn render_intrinsics(
                     return s.codePointAt(0);
                 }
             "),
+
+            Intrinsic::ValidateInt => output.push_str("
+                function validateInt(val, min, max, ty, context) {
+                    const isInt = typeof min === 'bigint' ? typeof val === 'bigint' : Number.isInteger(val);
+                    if (!isInt || val < min || val > max) \
+                        throw new TypeError(`${context}: invalid ${ty} value ${String(val)}, expected an integer from ${min} to ${max}`);
+                    return val;
+                }
+            "),
+
+            Intrinsic::ValidateRecord => output.push_str("
+                function validateRecord(val, fields, name, context) {
+                    if (val === null || typeof val !== 'object') \
+                        throw new TypeError(`${context}: expected record ${name}, got ${String(val)}`);
+                    for (const field of fields) {
+                        if (!(field in val)) \
+                            throw new TypeError(`${context}: record ${name} is missing the required field \"${field}\"`);
+                    }
+                    return val;
+                }
+            "),
+
+            Intrinsic::TraceArgs => output.push_str("
+                function traceArgs(args) {
+                    return Array.from(args, arg => arg !== null && typeof arg === 'object' && symbolRscHandle in arg ? arg[symbolRscHandle] : arg);
+                }
+            "),
+
+            // with instantiation the handler is passed as the `$trace` import
+            Intrinsic::TraceHandler => if instantiation {
+                output.push_str("
+                    let traceHandler = imports.$trace || null;
+                ")
+            } else {
+                output.push_str("
+                    let traceHandler = null;
+                ")
+            },
       }
     }
 
@@ -655,6 +724,7 @@ impl Intrinsic {
         &[
             // Intrinsic list exactly as below
             "base64Compile",
+            "borrowCallback",
             "clampGuest",
             "ComponentError",
             "curResourceBorrows",
@@ -701,6 +771,8 @@ impl Intrinsic {
             "toUint32",
             "toUint64",
             "toUint8",
+            "traceArgs",
+            "traceHandler",
             "utf16Decoder",
             "utf16Encode",
             "utf8Decoder",
@@ -708,6 +780,8 @@ impl Intrinsic {
             "utf8EncodedLen",
             "validateGuestChar",
             "validateHostChar",
+            "validateInt",
+            "validateRecord",
             // JS Globals / non intrinsic names
             "BigInt",
             "BigInt64Array",
@@ -734,6 +808,7 @@ impl Intrinsic {
     pub fn name(&self) -> &'static str {
         match self {
             Intrinsic::Base64Compile => "base64Compile",
+            Intrinsic::BorrowCallback => "borrowCallback",
             Intrinsic::ClampGuest => "clampGuest",
             Intrinsic::ComponentError => "ComponentError",
             Intrinsic::CurResourceBorrows => "curResourceBorrows",
@@ -781,6 +856,8 @@ impl Intrinsic {
             Intrinsic::ToUint16 => "toUint16",
             Intrinsic::ToUint32 => "toUint32",
             Intrinsic::ToUint8 => "toUint8",
+            Intrinsic::TraceArgs => "traceArgs",
+            Intrinsic::TraceHandler => "traceHandler",
             Intrinsic::Utf16Decoder => "utf16Decoder",
             Intrinsic::Utf16Encode => "utf16Encode",
             Intrinsic::Utf8Decoder => "utf8Decoder",
@@ -788,6 +865,8 @@ impl Intrinsic {
             Intrinsic::Utf8EncodedLen => "utf8EncodedLen",
             Intrinsic::ValidateGuestChar => "validateGuestChar",
             Intrinsic::ValidateHostChar => "validateHostChar",
+            Intrinsic::ValidateInt => "validateInt",
+            Intrinsic::ValidateRecord => "validateRecord",
         }
     }
 }
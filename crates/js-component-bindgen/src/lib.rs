@@ -1,5 +1,6 @@
 mod core;
 mod files;
+mod manifest;
 mod transpile_bindgen;
 mod ts_bindgen;
 
@@ -8,6 +9,7 @@ pub mod function_bindgen;
 pub mod intrinsics;
 pub mod names;
 pub mod source;
+pub use files::{DirectorySink, OutputSink};
 pub use transpile_bindgen::{BindingsMode, InstantiationMode, TranspileOpts};
 
 use anyhow::Result;
@@ -19,8 +21,12 @@ use wasmtime_environ::wasmparser::Validator;
 use wasmtime_environ::{PrimaryMap, ScopeVec, Tunables};
 use wit_component::DecodedWasm;
 
+use std::collections::BTreeSet;
 use ts_bindgen::ts_bindgen;
-use wit_parser::{Package, Resolve, Stability, Type, TypeDefKind, TypeId, WorldId};
+use wit_parser::{
+    FunctionKind, Handle, Package, Resolve, Stability, Type, TypeDefKind, TypeId, TypeOwner,
+    WorldId, WorldItem,
+};
 
 /// Calls [`write!`] with the passed arguments and unwraps the result.
 ///
@@ -65,15 +71,17 @@ pub fn generate_types(
     world_id: WorldId,
     opts: TranspileOpts,
 ) -> Result<Vec<(String, Vec<u8>)>, anyhow::Error> {
-    let mut files = files::Files::default();
+    let mut files_out: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut files = files::Files::new(&mut files_out);
+
+    if opts.errors_extend_error {
+        check_error_resources(&resolve)?;
+    }
 
     ts_bindgen(&name, &resolve, world_id, &opts, &mut files)
         .context("failed to generate Typescript bindings")?;
 
-    let mut files_out: Vec<(String, Vec<u8>)> = Vec::new();
-    for (name, source) in files.iter() {
-        files_out.push((name.to_string(), source.to_vec()));
-    }
+    files.finish()?;
     Ok(files_out)
 }
 
@@ -81,10 +89,29 @@ pub fn generate_types(
 /// Outputs the file map and import and export metadata for the Transpilation
 #[cfg(feature = "transpile-bindgen")]
 pub fn transpile(component: &[u8], opts: TranspileOpts) -> Result<Transpiled, anyhow::Error> {
+    let mut files = Vec::new();
+    let ComponentInfo { imports, exports } = transpile_to(component, opts, &mut files)?;
+    files.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(Transpiled {
+        files,
+        imports,
+        exports,
+    })
+}
+
+/// Generate the JS transpilation bindgen for a given Wasm component binary,
+/// writing each output file to the given sink as soon as it is complete
+/// Outputs the import and export metadata for the Transpilation
+#[cfg(feature = "transpile-bindgen")]
+pub fn transpile_to(
+    component: &[u8],
+    opts: TranspileOpts,
+    sink: &mut dyn OutputSink,
+) -> Result<ComponentInfo, anyhow::Error> {
     use wasmtime_environ::component::{Component, Translator};
 
     let name = opts.name.clone();
-    let mut files = files::Files::default();
+    let mut files = files::Files::new(sink);
 
     // Use the `wit-component` crate here to parse `binary` and discover
     // the type-level descriptions and `Resolve` corresponding to the
@@ -100,6 +127,13 @@ pub fn transpile(component: &[u8], opts: TranspileOpts) -> Result<Transpiled, an
         DecodedWasm::Component(resolve, world_id) => (resolve, world_id),
     };
 
+    if opts.flatten_exports {
+        esm_bindgen::flattened_export_interface(&resolve, world_id)?;
+    }
+    if opts.errors_extend_error {
+        check_error_resources(&resolve)?;
+    }
+
     // Components are complicated, there's no real way around that. To
     // handle all the work of parsing a component and figuring out how to
     // instantiate core wasm modules and such all the work is offloaded to
@@ -131,12 +165,6 @@ pub fn transpile(component: &[u8], opts: TranspileOpts) -> Result<Transpiled, an
     let mut wasmtime_component = Component::default();
     let types = types.finish(&mut wasmtime_component);
 
-    // Insert all core wasm modules into the generated `Files` which will
-    // end up getting used in the `generate_instantiate` method.
-    for (i, module) in modules.iter() {
-        files.push(&core_file_name(&name, i.as_u32()), module.wasm());
-    }
-
     if !opts.no_typescript {
         ts_bindgen(&name, &resolve, world_id, &opts, &mut files)
             .context("failed to generate Typescript bindings")?;
@@ -146,24 +174,8 @@ pub fn transpile(component: &[u8], opts: TranspileOpts) -> Result<Transpiled, an
         &name, &component, &modules, &types.0, &resolve, world_id, opts, &mut files,
     );
 
-    let mut files_out: Vec<(String, Vec<u8>)> = Vec::new();
-    for (name, source) in files.iter() {
-        files_out.push((name.to_string(), source.to_vec()));
-    }
-    Ok(Transpiled {
-        files: files_out,
-        imports,
-        exports,
-    })
-}
-
-fn core_file_name(name: &str, idx: u32) -> String {
-    let i_str = if idx == 0 {
-        String::from("")
-    } else {
-        (idx + 1).to_string()
-    };
-    format!("{}.core{i_str}.wasm", name)
+    files.finish()?;
+    Ok(ComponentInfo { imports, exports })
 }
 
 pub fn dealias(resolve: &Resolve, mut id: TypeId) -> TypeId {
@@ -175,6 +187,80 @@ pub fn dealias(resolve: &Resolve, mut id: TypeId) -> TypeId {
     }
 }
 
+/// The variants lifted into classes extending `Error` with
+/// `errors_extend_error`: variants named `error`, and variants used as the
+/// error case of a result
+fn error_variants(resolve: &Resolve) -> BTreeSet<TypeId> {
+    let mut error_types = BTreeSet::new();
+    for (id, ty) in resolve.types.iter() {
+        match &ty.kind {
+            TypeDefKind::Variant(_) if ty.name.as_deref() == Some("error") => {
+                error_types.insert(id);
+            }
+            TypeDefKind::Result(wit_parser::Result_ {
+                err: Some(Type::Id(err)),
+                ..
+            }) => {
+                let err = dealias(resolve, *err);
+                if let TypeDefKind::Variant(_) = &resolve.types[err].kind {
+                    error_types.insert(err);
+                }
+            }
+            _ => {}
+        }
+    }
+    error_types
+}
+
+/// Error resources are passed through `errors_extend_error` unchanged, so
+/// reject the resources whose `to-string` method would be expected to give
+/// the error message
+fn check_error_resources(resolve: &Resolve) -> Result<()> {
+    for (id, ty) in resolve.types.iter() {
+        let resource = match &ty.kind {
+            TypeDefKind::Resource if ty.name.as_deref() == Some("error") => id,
+            TypeDefKind::Result(wit_parser::Result_ {
+                err: Some(Type::Id(err)),
+                ..
+            }) => match &resolve.types[dealias(resolve, *err)].kind {
+                TypeDefKind::Handle(Handle::Own(resource) | Handle::Borrow(resource)) => {
+                    dealias(resolve, *resource)
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let resource_ty = &resolve.types[resource];
+        let has_to_string = match resource_ty.owner {
+            TypeOwner::Interface(iface) => {
+                resolve.interfaces[iface].functions.values().any(|func| {
+                    func.kind == FunctionKind::Method(resource) && func.item_name() == "to-string"
+                })
+            }
+            TypeOwner::World(world) => {
+                let world = &resolve.worlds[world];
+                world.imports.values().chain(world.exports.values()).any(|item| {
+                    matches!(item, WorldItem::Function(func)
+                        if func.kind == FunctionKind::Method(resource) && func.item_name() == "to-string")
+                })
+            }
+            TypeOwner::None => false,
+        };
+        if has_to_string {
+            let name = resource_ty.name.as_deref().unwrap();
+            let name = match resource_ty.owner {
+                TypeOwner::Interface(iface) => match resolve.id_of(iface) {
+                    Some(iface_id) => format!("{iface_id}#{name}"),
+                    None => name.to_string(),
+                },
+                _ => name.to_string(),
+            };
+            bail!("errors extending Error are not supported for the error resource `{name}`, whose message would come from its `to-string` method");
+        }
+    }
+    Ok(())
+}
+
 /// Check if an item (usually some form of [`WorldItem`]) should be allowed through the feature gate
 /// of a given package.
 fn feature_gate_allowed(
@@ -0,0 +1,215 @@
+use crate::transpile_bindgen::map_import;
+use crate::TranspileOpts;
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use wit_parser::{FunctionKind, InterfaceId, Resolve, TypeDefKind, WorldId, WorldItem};
+
+/// An import or export of the component, or an item of an interface
+struct Entry {
+    kind: &'static str,
+    /// JS identifier of the item, or for imports of a whole interface the
+    /// member read off of the import specifier (if any)
+    js: Option<String>,
+    specifier: Option<String>,
+    items: Option<BTreeMap<String, Entry>>,
+}
+
+/// Render the `{name}.manifest.json` description of the component imports
+/// and exports.
+///
+/// Entries are sorted by their WIT name so that the output is stable to diff.
+/// `export_aliases` are the (JS export name, canonical export name) pairs of
+/// the ESM bindgen.
+pub(crate) fn render_manifest(
+    resolve: &Resolve,
+    world: WorldId,
+    opts: &TranspileOpts,
+    export_aliases: &[(&str, &str)],
+) -> String {
+    let world = &resolve.worlds[world];
+
+    let mut imports = BTreeMap::new();
+    for (key, item) in &world.imports {
+        let name = resolve.name_world_key(key);
+        let (specifier, member) = map_import(&opts.map, &name);
+        let mut entry = world_item_entry(resolve, &name, item);
+        entry.specifier = Some(specifier);
+        if let WorldItem::Interface { .. } = item {
+            entry.js = member;
+        }
+        imports.insert(name, entry);
+    }
+
+    let mut exports = BTreeMap::new();
+    for (key, item) in &world.exports {
+        let name = resolve.name_world_key(key);
+        let mut entry = world_item_entry(resolve, &name, item);
+        if let WorldItem::Interface { .. } = item {
+            // prefer the identifier alias over the string export name
+            let alias = export_aliases
+                .iter()
+                .find(|(alias, canon)| *canon == name && *alias != name)
+                .map_or(name.as_str(), |(alias, _)| alias);
+            entry.js = Some(alias.to_string());
+        }
+        exports.insert(name, entry);
+    }
+
+    let mut out = String::new();
+    out.push_str("{\n  \"imports\": ");
+    render_entries(&mut out, &imports, 1);
+    out.push_str(",\n  \"exports\": ");
+    render_entries(&mut out, &exports, 1);
+    out.push_str("\n}\n");
+    out
+}
+
+fn world_item_entry(resolve: &Resolve, name: &str, item: &WorldItem) -> Entry {
+    match item {
+        WorldItem::Interface { id, .. } => Entry {
+            kind: "interface",
+            js: None,
+            specifier: None,
+            items: Some(interface_items(resolve, *id)),
+        },
+        WorldItem::Function(_) => Entry {
+            kind: "function",
+            js: Some(name.to_lower_camel_case()),
+            specifier: None,
+            items: None,
+        },
+        WorldItem::Type(id) => Entry {
+            kind: type_kind(&resolve.types[*id].kind),
+            js: Some(name.to_upper_camel_case()),
+            specifier: None,
+            items: None,
+        },
+    }
+}
+
+fn interface_items(resolve: &Resolve, id: InterfaceId) -> BTreeMap<String, Entry> {
+    let iface = &resolve.interfaces[id];
+    let mut items = BTreeMap::new();
+    for (name, ty) in &iface.types {
+        items.insert(
+            name.to_string(),
+            Entry {
+                kind: type_kind(&resolve.types[*ty].kind),
+                js: Some(name.to_upper_camel_case()),
+                specifier: None,
+                items: None,
+            },
+        );
+    }
+    for (name, func) in &iface.functions {
+        let kind = match func.kind {
+            FunctionKind::Freestanding => "function",
+            FunctionKind::Method(_) => "method",
+            FunctionKind::Static(_) => "static",
+            FunctionKind::Constructor(_) => "constructor",
+        };
+        let js = match func.kind {
+            FunctionKind::Freestanding => name.to_lower_camel_case(),
+            FunctionKind::Method(ty) => format!(
+                "{}.prototype.{}",
+                resolve.types[ty]
+                    .name
+                    .as_ref()
+                    .unwrap()
+                    .to_upper_camel_case(),
+                func.item_name().to_lower_camel_case()
+            ),
+            FunctionKind::Static(ty) => format!(
+                "{}.{}",
+                resolve.types[ty]
+                    .name
+                    .as_ref()
+                    .unwrap()
+                    .to_upper_camel_case(),
+                func.item_name().to_lower_camel_case()
+            ),
+            FunctionKind::Constructor(ty) => resolve.types[ty]
+                .name
+                .as_ref()
+                .unwrap()
+                .to_upper_camel_case(),
+        };
+        items.insert(
+            name.to_string(),
+            Entry {
+                kind,
+                js: Some(js),
+                specifier: None,
+                items: None,
+            },
+        );
+    }
+    items
+}
+
+fn type_kind(kind: &TypeDefKind) -> &'static str {
+    match kind {
+        TypeDefKind::Record(_) => "record",
+        TypeDefKind::Resource => "resource",
+        TypeDefKind::Handle(_) => "handle",
+        TypeDefKind::Flags(_) => "flags",
+        TypeDefKind::Tuple(_) => "tuple",
+        TypeDefKind::Variant(_) => "variant",
+        TypeDefKind::Enum(_) => "enum",
+        TypeDefKind::Option(_) => "option",
+        TypeDefKind::Result(_) => "result",
+        TypeDefKind::List(_) => "list",
+        TypeDefKind::Future(_) => "future",
+        TypeDefKind::Stream(_) => "stream",
+        TypeDefKind::Type(_) => "type",
+        TypeDefKind::Unknown => unreachable!(),
+    }
+}
+
+fn render_entries(out: &mut String, entries: &BTreeMap<String, Entry>, depth: usize) {
+    if entries.is_empty() {
+        out.push_str("[]");
+        return;
+    }
+    let indent = "  ".repeat(depth);
+    out.push_str("[\n");
+    for (i, (name, entry)) in entries.iter().enumerate() {
+        if i > 0 {
+            out.push_str(",\n");
+        }
+        write!(out, "{indent}  {{\n{indent}    \"name\": ").unwrap();
+        json_string(out, name);
+        write!(out, ",\n{indent}    \"kind\": \"{}\"", entry.kind).unwrap();
+        if let Some(js) = &entry.js {
+            write!(out, ",\n{indent}    \"js\": ").unwrap();
+            json_string(out, js);
+        }
+        if let Some(specifier) = &entry.specifier {
+            write!(out, ",\n{indent}    \"specifier\": ").unwrap();
+            json_string(out, specifier);
+        }
+        if let Some(items) = &entry.items {
+            write!(out, ",\n{indent}    \"items\": ").unwrap();
+            render_entries(out, items, depth + 2);
+        }
+        write!(out, "\n{indent}  }}").unwrap();
+    }
+    write!(out, "\n{indent}]").unwrap();
+}
+
+fn json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
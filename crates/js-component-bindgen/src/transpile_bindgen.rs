@@ -5,6 +5,7 @@ use crate::function_bindgen::{
     ErrHandling, FunctionBindgen, ResourceData, ResourceMap, ResourceTable,
 };
 use crate::intrinsics::{render_intrinsics, Intrinsic};
+use crate::manifest::render_manifest;
 use crate::names::{is_js_reserved_word, maybe_quote_id, maybe_quote_member, LocalNames};
 use crate::source;
 use crate::{uwrite, uwriteln};
@@ -68,6 +69,60 @@ pub struct TranspileOpts {
     /// Whether to output core Wasm utilizing multi-memory or to polyfill
     /// this handling.
     pub multi_memory: bool,
+    /// Whether to brand resource classes in the generated TypeScript with a
+    /// `__brand` member naming the resource and its interface, making them
+    /// nominally typed.
+    pub branded_resources: bool,
+    /// Whether to annotate deprecated and unstable items in the generated
+    /// TypeScript with `@deprecated` and `@experimental` JSDoc tags.
+    pub stability_annotations: bool,
+    /// In instantiation mode, additionally export an `instantiateSync` function
+    /// which instantiates from already compiled core modules.
+    pub instantiate_sync: bool,
+    /// Lift error variants (variants named `error`, or used as the error
+    /// case of a result) into generated classes extending `Error`.
+    pub errors_extend_error: bool,
+    /// Make exported functions and resource methods async, so that they
+    /// always return a promise.
+    pub wrap_sync_in_promise: bool,
+    /// Emit an `is{Variant}{Case}` type guard for each case of the variants
+    /// defined in interfaces, implemented in `interfaces/*.js`.
+    pub variant_type_guards: bool,
+    /// Emit an `equals{Type}` structural equality helper for each record,
+    /// variant, tuple and list type defined in interfaces, implemented in
+    /// `interfaces/*.js`.
+    pub equality_helpers: bool,
+    /// Emit a `match{Variant}(v, handlers)` function for each variant defined
+    /// in interfaces, calling the handler of the case of `v`, implemented in
+    /// `interfaces/*.js`.
+    pub variant_match_helpers: bool,
+    /// Export the members of the single exported interface directly, instead
+    /// of through the interface namespace object.
+    pub flatten_exports: bool,
+    /// Check lowered values against their WIT types at runtime, throwing a
+    /// `TypeError` naming the function for out of range integers, unknown
+    /// enum cases and records missing required fields.
+    pub runtime_validation: bool,
+    /// Lift the `none` case of options as `null` instead of `undefined`, and
+    /// type them as `T | null`. Options of options still use the
+    /// `{ tag: 'none' }` / `{ tag: 'some', val }` wrapper for the outer level.
+    pub nullable_options: bool,
+    /// Add an optional trailing `{ signal }` argument to the async exports of
+    /// `wrap_sync_in_promise`, rejecting with the abort reason when the
+    /// `AbortSignal` is aborted before the call returns.
+    pub abort_signals: bool,
+    /// Emit a `{Enum}Cases` array of the case names of each enum defined in
+    /// interfaces, in WIT order, implemented in `interfaces/*.js`, and derive
+    /// the enum type from it.
+    pub enum_case_arrays: bool,
+    /// Pass the borrowed resources received by imports, other than the method
+    /// receiver, as a `withBorrow(callback)` function instead, which calls
+    /// `callback` with the resource and throws once the import call returns.
+    /// WASI imports are not affected.
+    pub borrow_callbacks: bool,
+    /// Additionally output a `{name}.manifest.json` describing the imports
+    /// and exports of the component and their JS identifiers.
+    pub manifest: bool,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -107,9 +162,6 @@ struct JsBindgen<'a> {
     /// as a type-description of the input/output interfaces.
     src: Source,
 
-    /// Core module count
-    core_module_cnt: usize,
-
     /// Various options for code generation.
     opts: &'a TranspileOpts,
 
@@ -131,14 +183,12 @@ pub fn transpile_bindgen(
         local_names: LocalNames::default(),
         src: Source::default(),
         esm_bindgen: EsmBindgen::default(),
-        core_module_cnt: 0,
         opts: &opts,
         all_intrinsics: BTreeSet::new(),
     };
     bindgen
         .local_names
         .exclude_globals(Intrinsic::get_global_names());
-    bindgen.core_module_cnt = modules.len();
 
     // bindings is the actual `instantiate` method itself, created by this
     // structure.
@@ -159,6 +209,8 @@ pub fn transpile_bindgen(
         lowering_options: Default::default(),
         used_instance_flags: Default::default(),
         defined_resource_classes: Default::default(),
+        error_classes: Default::default(),
+        used_error_classes: Default::default(),
         imports_resource_types: Default::default(),
         exports_resource_types: Default::default(),
         resources_initialized: (0..component.component.num_resources)
@@ -175,14 +227,24 @@ pub fn transpile_bindgen(
     let mut intrinsic_definitions = source::Source::default();
 
     instantiator.resource_definitions(&mut intrinsic_definitions);
+    instantiator.error_class_definitions(&mut intrinsic_definitions);
     instantiator.instance_flags();
 
     instantiator.gen.src.js(&instantiator.src.js);
     instantiator.gen.src.js_init(&instantiator.src.js_init);
 
-    instantiator
-        .gen
-        .finish_component(name, files, &opts, intrinsic_definitions);
+    instantiator.gen.finish_component(
+        name,
+        instantiator.modules,
+        files,
+        &opts,
+        intrinsic_definitions,
+    );
+
+    if opts.manifest {
+        let manifest = render_manifest(resolve, id, &opts, &instantiator.gen.esm_bindgen.exports());
+        files.push(&format!("{name}.manifest.json"), manifest.as_bytes());
+    }
 
     let exports = instantiator
         .gen
@@ -217,6 +279,7 @@ impl<'a> JsBindgen<'a> {
     fn finish_component(
         &mut self,
         name: &str,
+        modules: &PrimaryMap<StaticModuleIndex, core::Translation<'_>>,
         files: &mut Files,
         opts: &TranspileOpts,
         intrinsic_definitions: source::Source,
@@ -224,9 +287,11 @@ impl<'a> JsBindgen<'a> {
         let mut output = source::Source::default();
         let mut compilation_promises = source::Source::default();
 
-        // Setup the compilation data and compilation promises
+        // Setup the compilation data and compilation promises, writing out
+        // each core module that is not inlined as it is reached
         let mut removed = BTreeSet::new();
-        for i in 0..self.core_module_cnt {
+        for (i, module) in modules.iter() {
+            let i = i.as_u32() as usize;
             let local_name = format!("module{}", i);
             let mut name_idx = core_file_name(name, i as u32);
             if self.opts.instantiation.is_some() {
@@ -234,32 +299,36 @@ impl<'a> JsBindgen<'a> {
                     compilation_promises,
                     "const {local_name} = getCoreModule('{name_idx}');"
                 );
-            } else if files.get_size(&name_idx).unwrap() < self.opts.base64_cutoff {
+                files.write(&name_idx, module.wasm());
+            } else if module.wasm().len() < self.opts.base64_cutoff {
                 assert!(removed.insert(i));
-                let data = files.remove(&name_idx).unwrap();
                 uwriteln!(
                     compilation_promises,
                     "const {local_name} = {}('{}');",
                     self.intrinsic(Intrinsic::Base64Compile),
-                    general_purpose::STANDARD_NO_PAD.encode(&data),
+                    general_purpose::STANDARD_NO_PAD.encode(module.wasm()),
                 );
             } else {
                 // Maintain numerical file orderings when a previous file was
                 // inlined
                 if let Some(&replacement) = removed.iter().next() {
                     assert!(removed.remove(&replacement) && removed.insert(i));
-                    let data = files.remove(&name_idx).unwrap();
                     name_idx = core_file_name(name, replacement as u32);
-                    files.push(&name_idx, &data);
                 }
                 uwriteln!(
                     compilation_promises,
                     "const {local_name} = {}(new URL('./{name_idx}', import.meta.url));",
                     self.intrinsic(Intrinsic::FetchCompile)
                 );
+                files.write(&name_idx, module.wasm());
             }
         }
 
+        // the trace handler can be set even if no function is traced
+        if self.opts.tracing {
+            self.intrinsic(Intrinsic::TraceHandler);
+        }
+
         let js_intrinsics = render_intrinsics(
             &mut self.all_intrinsics,
             self.opts.no_nodejs_compat,
@@ -334,6 +403,22 @@ impl<'a> JsBindgen<'a> {
                 &self.src.js_init as &str,
                 &self.src.js as &str,
             );
+            if self.opts.instantiate_sync {
+                uwrite!(
+                    output,
+                    "
+                        export function instantiateSync(compiledModules, imports) {{
+                            return instantiate(path => {{
+                                const module = compiledModules[path];
+                                if (!(module instanceof WebAssembly.Module)) {{
+                                    throw new TypeError(`Missing compiled core module for ${{path}}`);
+                                }}
+                                return module;
+                            }}, imports, (module, importObject) => new WebAssembly.Instance(module, importObject));
+                        }}
+                    "
+                );
+            }
         } else {
             let (maybe_init_export, maybe_init) =
                 if self.opts.tla_compat && matches!(opts.instantiation, None) {
@@ -401,6 +486,17 @@ impl<'a> JsBindgen<'a> {
                 &mut self.local_names,
                 opts,
             );
+
+            if self.opts.tracing {
+                uwrite!(
+                    output,
+                    "
+                    export function $setTrace (handler) {{
+                        traceHandler = handler;
+                    }}
+                    "
+                );
+            }
         }
 
         let mut bytes = output.as_bytes();
@@ -440,6 +536,9 @@ struct Instantiator<'a, 'b> {
     /// Instance flags which references have been emitted externally at least once.
     used_instance_flags: RefCell<BTreeSet<RuntimeComponentInstanceIndex>>,
     defined_resource_classes: BTreeSet<String>,
+    /// Error variant types lifted as `Error` subclasses, with their local class names
+    error_classes: BTreeMap<TypeId, String>,
+    used_error_classes: BTreeSet<TypeId>,
     lowering_options:
         PrimaryMap<LoweredIndex, (&'a CanonicalOptions, TrampolineIndex, TypeFuncIndex)>,
 }
@@ -543,7 +642,20 @@ impl<'a> Instantiator<'a, '_> {
                 WorldItem::Type(_) => unreachable!(),
             }
         }
+
+        if self.gen.opts.errors_extend_error {
+            for id in crate::error_variants(self.resolve) {
+                let name = self.resolve.types[id].name.as_ref().unwrap();
+                let local_name = self
+                    .gen
+                    .local_names
+                    .create_once(&name.to_upper_camel_case())
+                    .to_string();
+                self.error_classes.insert(id, local_name);
+            }
+        }
     }
+
     fn instantiate(&mut self) {
         for (i, trampoline) in self.translation.trampolines.iter() {
             let Trampoline::LowerImport {
@@ -623,6 +735,25 @@ impl<'a> Instantiator<'a, '_> {
         }
     }
 
+    fn error_class_definitions(&self, definitions: &mut source::Source) {
+        for id in &self.used_error_classes {
+            let local_name = &self.error_classes[id];
+            let name = self.resolve.types[*id].name.as_ref().unwrap();
+            let name = name.to_upper_camel_case();
+            uwriteln!(definitions, "\nclass {local_name} extends Error {{");
+            uwriteln!(
+                definitions,
+                "constructor (tag, val) {{
+                        super(typeof val === 'string' ? `${{tag}}: ${{val}}` : tag);
+                        this.name = '{name}';
+                        this.tag = tag;
+                        if (arguments.length > 1) this.val = val;
+                    }}
+                }}"
+            );
+        }
+    }
+
     fn resource_definitions(&mut self, definitions: &mut source::Source) {
         // It is theoretically possible for locally defined resources used in no functions
         // to still be exported
@@ -1525,6 +1656,17 @@ impl<'a> Instantiator<'a, '_> {
             .post_return
             .map(|idx| format!("postReturn{}", idx.as_u32()));
 
+        // async exports take a trailing `{ signal }` options argument
+        let abort_signal = self.gen.opts.abort_signals
+            && self.gen.opts.wrap_sync_in_promise
+            && matches!(abi, AbiVariant::GuestExport)
+            && !matches!(func.kind, FunctionKind::Constructor(_));
+
+        // the WASI shims take borrows directly
+        let borrow_callbacks = self.gen.opts.borrow_callbacks
+            && matches!(abi, AbiVariant::GuestImport)
+            && !module_name.is_some_and(|name| name.starts_with("wasi:"));
+
         self.src.js("(");
         let mut params = Vec::new();
         let mut first = true;
@@ -1542,6 +1684,9 @@ impl<'a> Instantiator<'a, '_> {
             self.src.js(&param);
             params.push(param);
         }
+        if abort_signal {
+            self.src.js(if first { "options" } else { ", options" });
+        }
         uwriteln!(self.src.js, ") {{");
 
         let tracing_prefix = format!(
@@ -1550,7 +1695,22 @@ impl<'a> Instantiator<'a, '_> {
             func.name
         );
 
+        let tracing_target = format!(
+            "module: '{}', function: '{}'",
+            module_name.unwrap_or("<no module>"),
+            func.name
+        );
+
+        let validation_context = match module_name {
+            Some(module_name) => format!("{module_name}#{}", func.name),
+            None => func.name.to_string(),
+        };
+
+        // calls are passed to the trace handler when one is set, and logged
+        // otherwise
         if self.gen.opts.tracing {
+            let trace_handler = self.gen.intrinsic(Intrinsic::TraceHandler);
+            let trace_args = self.gen.intrinsic(Intrinsic::TraceArgs);
             let event_fields = func
                 .params
                 .iter()
@@ -1559,7 +1719,8 @@ impl<'a> Instantiator<'a, '_> {
                 .collect::<Vec<String>>();
             uwriteln!(
                 self.src.js,
-                "console.error(`{tracing_prefix} call {}`);",
+                "if ({trace_handler}) {trace_handler}({{ type: 'call', {tracing_target}, args: {trace_args}(arguments) }});
+                else console.error(`{tracing_prefix} call {}`);",
                 event_fields.join(", ")
             );
         }
@@ -1577,6 +1738,14 @@ impl<'a> Instantiator<'a, '_> {
             );
         }
 
+        if abort_signal {
+            uwriteln!(self.src.js, "options?.signal?.throwIfAborted();");
+        }
+
+        if self.gen.opts.tracing {
+            uwriteln!(self.src.js, "try {{");
+        }
+
         let mut f = FunctionBindgen {
             resource_map: &resource_map,
             cur_resource_borrows: false,
@@ -1605,6 +1774,20 @@ impl<'a> Instantiator<'a, '_> {
             } else {
                 None
             },
+            tracing_target: if self.gen.opts.tracing {
+                Some(&tracing_target)
+            } else {
+                None
+            },
+            trace_return: false,
+            validation_context: if self.gen.opts.runtime_validation {
+                Some(&validation_context)
+            } else {
+                None
+            },
+            nullable_options: self.gen.opts.nullable_options,
+            abort_signal,
+            borrow_callbacks,
             encoding: match opts.string_encoding {
                 component::StringEncoding::Utf8 => StringEncoding::UTF8,
                 component::StringEncoding::Utf16 => StringEncoding::UTF16,
@@ -1612,6 +1795,8 @@ impl<'a> Instantiator<'a, '_> {
             },
             src: source::Source::default(),
             resolve: self.resolve,
+            error_classes: &self.error_classes,
+            used_error_classes: &mut self.used_error_classes,
         };
         abi::call(
             self.resolve,
@@ -1624,6 +1809,16 @@ impl<'a> Instantiator<'a, '_> {
             &mut f,
         );
         self.src.js(&f.src);
+        if self.gen.opts.tracing {
+            let trace_handler = self.gen.intrinsic(Intrinsic::TraceHandler);
+            uwriteln!(
+                self.src.js,
+                "}} catch (e) {{
+                    if ({trace_handler}) {trace_handler}({{ type: 'error', {tracing_target}, error: e }});
+                    throw e;
+                }}"
+            );
+        }
         self.src.js("}");
     }
 
@@ -1871,6 +2066,19 @@ impl<'a> Instantiator<'a, '_> {
                             );
                         }
                     }
+
+                    // the classes errors are lifted as, for `instanceof` checks
+                    for ty in self.resolve.interfaces[id].types.values() {
+                        if let Some(local_name) = self.error_classes.get(ty) {
+                            self.used_error_classes.insert(*ty);
+                            let name = self.resolve.types[*ty].name.as_ref().unwrap();
+                            self.gen.esm_bindgen.add_export_binding(
+                                Some(export_name),
+                                local_name.to_string(),
+                                name.to_upper_camel_case(),
+                            );
+                        }
+                    }
                 }
 
                 // ignore type exports for now
@@ -1892,14 +2100,21 @@ impl<'a> Instantiator<'a, '_> {
         export_name: &String,
         resource_map: &ResourceMap,
     ) {
+        let maybe_async = if self.gen.opts.wrap_sync_in_promise {
+            "async "
+        } else {
+            ""
+        };
         match func.kind {
-            FunctionKind::Freestanding => uwrite!(self.src.js, "\nfunction {local_name}"),
+            FunctionKind::Freestanding => {
+                uwrite!(self.src.js, "\n{maybe_async}function {local_name}")
+            }
             FunctionKind::Method(_) => {
                 self.ensure_local_resource_class(local_name.to_string());
                 let method_name = func.item_name().to_lower_camel_case();
                 uwrite!(
                     self.src.js,
-                    "\n{local_name}.prototype.{method_name} = function {}",
+                    "\n{local_name}.prototype.{method_name} = {maybe_async}function {}",
                     if !is_js_reserved_word(&method_name) {
                         method_name.to_string()
                     } else {
@@ -1912,7 +2127,7 @@ impl<'a> Instantiator<'a, '_> {
                 let method_name = func.item_name().to_lower_camel_case();
                 uwrite!(
                     self.src.js,
-                    "\n{local_name}.{method_name} = function {}",
+                    "\n{local_name}.{method_name} = {maybe_async}function {}",
                     if !is_js_reserved_word(&method_name) {
                         method_name.to_string()
                     } else {
@@ -1974,7 +2189,10 @@ impl Source {
     }
 }
 
-fn map_import(map: &Option<HashMap<String, String>>, impt: &str) -> (String, Option<String>) {
+pub(crate) fn map_import(
+    map: &Option<HashMap<String, String>>,
+    impt: &str,
+) -> (String, Option<String>) {
     let impt_sans_version = match impt.find('@') {
         Some(version_idx) => &impt[0..version_idx],
         None => impt,
@@ -2000,42 +2218,95 @@ fn map_import(map: &Option<HashMap<String, String>>, impt: &str) -> (String, Opt
                 (mapping.into(), None)
             };
         }
+        // the most specific wildcard mapping (with the longest literal part) wins
+        let mut best: Option<(usize, &String, String)> = None;
         for (key, mapping) in map {
-            if let Some(wildcard_idx) = key.find('*') {
-                let lhs = &key[0..wildcard_idx];
-                let rhs = &key[wildcard_idx + 1..];
-                if impt_sans_version.starts_with(lhs) && impt_sans_version.ends_with(rhs) {
-                    let matched = &impt_sans_version[wildcard_idx
-                        ..wildcard_idx + impt_sans_version.len() - lhs.len() - rhs.len()];
-                    let mapping = mapping.replace('*', matched);
-                    return if let Some(hash_idx) = mapping[1..].find('#') {
-                        (
-                            mapping[0..hash_idx + 1].to_string(),
-                            Some(mapping[hash_idx + 2..].into()),
-                        )
-                    } else {
-                        (mapping, None)
-                    };
-                }
-                if impt.starts_with(lhs) && impt.ends_with(rhs) {
-                    let matched =
-                        &impt[wildcard_idx..wildcard_idx + impt.len() - lhs.len() - rhs.len()];
-                    let mapping = mapping.replace('*', matched);
-                    return if let Some(hash_idx) = mapping[1..].find('#') {
-                        (
-                            mapping[0..hash_idx + 1].to_string(),
-                            Some(mapping[hash_idx + 2..].into()),
-                        )
-                    } else {
-                        (mapping, None)
-                    };
+            if !key.contains('*') {
+                continue;
+            }
+            let Some(captures) =
+                match_wildcards(key, impt_sans_version).or_else(|| match_wildcards(key, impt))
+            else {
+                continue;
+            };
+            let specificity = key.len() - key.matches('*').count();
+            if let Some((best_specificity, best_key, _)) = &best {
+                if specificity < *best_specificity
+                    || specificity == *best_specificity && key > *best_key
+                {
+                    continue;
                 }
             }
+            best = Some((specificity, key, expand_wildcards(mapping, &captures)));
+        }
+        if let Some((_, _, mapping)) = best {
+            return if let Some(hash_idx) = mapping[1..].find('#') {
+                (
+                    mapping[0..hash_idx + 1].to_string(),
+                    Some(mapping[hash_idx + 2..].into()),
+                )
+            } else {
+                (mapping, None)
+            };
         }
     }
     (impt_sans_version.to_string(), None)
 }
 
+/// Match a name against a pattern containing one or more `*` wildcards,
+/// returning the text matched by each wildcard in order
+fn match_wildcards<'a>(pattern: &str, name: &'a str) -> Option<Vec<&'a str>> {
+    let mut parts = pattern.split('*');
+    let mut rest = name.strip_prefix(parts.next().unwrap())?;
+    let parts = parts.collect::<Vec<_>>();
+    let mut captures = Vec::with_capacity(parts.len());
+    for (i, part) in parts.iter().enumerate() {
+        if i == parts.len() - 1 {
+            // the final literal part must match the end of the name
+            let captured = rest.strip_suffix(part)?;
+            captures.push(captured);
+        } else {
+            let part_idx = rest.find(part)?;
+            captures.push(&rest[0..part_idx]);
+            rest = &rest[part_idx + part.len()..];
+        }
+    }
+    Some(captures)
+}
+
+/// Expand the wildcard captures into a mapping target, where `#N` refers to
+/// the Nth capture, and each `*` to the next capture in order (so that
+/// with a single wildcard, every `*` is the matched text)
+fn expand_wildcards(mapping: &str, captures: &[&str]) -> String {
+    let mut expanded = String::with_capacity(mapping.len());
+    let mut next_capture = 0;
+    let mut chars = mapping.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '*' => {
+                expanded.push_str(captures[next_capture.min(captures.len() - 1)]);
+                next_capture += 1;
+            }
+            '#' if chars.peek().is_some_and(|(_, c)| c.is_ascii_digit()) => {
+                let mut end = idx + 1;
+                while let Some((digit_idx, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    end = digit_idx + 1;
+                }
+                match mapping[idx + 1..end]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| captures.get(n.wrapping_sub(1)))
+                {
+                    Some(captured) => expanded.push_str(captured),
+                    None => expanded.push_str(&mapping[idx..end]),
+                }
+            }
+            c => expanded.push(c),
+        }
+    }
+    expanded
+}
+
 pub fn parse_world_key(name: &str) -> Option<(&str, &str, &str)> {
     let registry_idx = match name.find(':') {
         Some(idx) => idx,
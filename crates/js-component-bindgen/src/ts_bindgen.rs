@@ -1,6 +1,9 @@
+use crate::esm_bindgen::{flattened_export_interface, interface_export_members};
 use crate::files::Files;
-use crate::function_bindgen::{array_ty, as_nullable, maybe_null};
-use crate::names::{is_js_identifier, maybe_quote_id, LocalNames, RESERVED_KEYWORDS};
+use crate::function_bindgen::{array_ty, as_borrow, as_nullable, maybe_null};
+use crate::names::{
+    is_js_identifier, maybe_quote_id, maybe_quote_member, LocalNames, RESERVED_KEYWORDS,
+};
 use crate::source::Source;
 use crate::transpile_bindgen::{parse_world_key, InstantiationMode, TranspileOpts};
 use crate::{dealias, feature_gate_allowed, uwrite, uwriteln};
@@ -8,11 +11,11 @@ use anyhow::{Context as _, Result};
 use heck::*;
 use log::debug;
 use std::collections::btree_map::Entry;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Write;
 use wit_bindgen_core::wit_parser::{
     Docs, Enum, Flags, Function, FunctionKind, Handle, InterfaceId, Record, Resolve, Result_,
-    Tuple, Type, TypeDefKind, TypeId, TypeOwner, Variant, WorldId, WorldItem, WorldKey,
+    Stability, Tuple, Type, TypeDefKind, TypeId, TypeOwner, Variant, WorldId, WorldItem, WorldKey,
 };
 
 struct TsBindgen {
@@ -25,10 +28,48 @@ struct TsBindgen {
     interface_names: LocalNames,
     local_names: LocalNames,
 
+    /// Whether the functions of each generated interface file return promises
+    /// and take borrow callbacks, and whether it declares error classes, by
+    /// file name
+    interface_flavors: HashMap<String, (bool, bool, bool)>,
+
     /// TypeScript definitions which will become the import object
     import_object: Source,
     /// TypeScript definitions which will become the export object
     export_object: Source,
+
+    /// Whether resource classes should be nominally branded
+    branded_resources: bool,
+
+    /// Whether to annotate deprecated and unstable items in their docs
+    stability_annotations: bool,
+
+    /// Whether exported functions return promises
+    wrap_sync_in_promise: bool,
+
+    /// Whether to emit a type guard function for each variant case
+    variant_type_guards: bool,
+
+    /// Whether to emit a structural equality function for each complex type
+    equality_helpers: bool,
+
+    /// Whether to emit a match function dispatching on the case of each variant
+    variant_match_helpers: bool,
+
+    /// Whether option `none` values are lifted as `null`
+    nullable_options: bool,
+
+    /// Whether async exports take a trailing `{ signal }` options argument
+    abort_signals: bool,
+
+    /// Whether to emit an array of the case names of each enum
+    enum_case_arrays: bool,
+
+    /// Whether imports receive borrows through a callback
+    borrow_callbacks: bool,
+
+    /// The error variants lifted as classes extending `Error`
+    error_variants: BTreeSet<TypeId>,
 }
 
 /// Used to generate a `*.d.ts` file for each imported and exported interface for
@@ -44,6 +85,31 @@ struct TsInterface<'a> {
     needs_ty_result: bool,
     local_names: LocalNames,
     resources: BTreeMap<String, TsInterface<'a>>,
+    branded_resources: bool,
+    stability_annotations: bool,
+    /// Whether function results are wrapped in `Promise`
+    promise_results: bool,
+    /// Whether to emit a type guard function for each variant case
+    variant_type_guards: bool,
+    /// Whether to emit a structural equality function for each complex type
+    equality_helpers: bool,
+    /// Whether to emit a match function dispatching on the case of each variant
+    variant_match_helpers: bool,
+    /// Whether option `none` values are lifted as `null`
+    nullable_options: bool,
+    /// Whether promise returning functions take a trailing `{ signal }`
+    /// options argument
+    abort_signals: bool,
+    /// Whether to emit an array of the case names of each enum
+    enum_case_arrays: bool,
+    /// Whether borrow params are received through a callback
+    borrow_callbacks: bool,
+    /// JS implementations of the emitted type guard, equality and match functions
+    helpers_js: Source,
+    /// Names of the equality functions emitted so far, by type
+    equality_helper_names: BTreeMap<TypeId, String>,
+    /// The brand of a resource class, qualified by the interface defining it
+    brand: String,
 }
 
 pub fn ts_bindgen(
@@ -57,8 +123,24 @@ pub fn ts_bindgen(
         src: Source::default(),
         interface_names: LocalNames::default(),
         local_names: LocalNames::default(),
+        interface_flavors: HashMap::new(),
         import_object: Source::default(),
         export_object: Source::default(),
+        branded_resources: opts.branded_resources,
+        stability_annotations: opts.stability_annotations,
+        wrap_sync_in_promise: opts.wrap_sync_in_promise,
+        variant_type_guards: opts.variant_type_guards,
+        equality_helpers: opts.equality_helpers,
+        variant_match_helpers: opts.variant_match_helpers,
+        nullable_options: opts.nullable_options,
+        abort_signals: opts.abort_signals,
+        enum_case_arrays: opts.enum_case_arrays,
+        borrow_callbacks: opts.borrow_callbacks,
+        error_variants: if opts.errors_extend_error {
+            crate::error_variants(resolve)
+        } else {
+            BTreeSet::new()
+        },
     };
 
     let world = &resolve.worlds[id];
@@ -176,6 +258,11 @@ pub fn ts_bindgen(
     }
 
     let mut funcs = Vec::new();
+    let flattened = if opts.flatten_exports {
+        Some(flattened_export_interface(resolve, id)?)
+    } else {
+        None
+    };
     let mut seen_names = HashSet::new();
     let mut export_aliases: Vec<(String, String)> = Vec::new();
 
@@ -214,14 +301,18 @@ pub fn ts_bindgen(
                 }
 
                 seen_names.insert(export_name.to_string());
+                let flatten = flattened == Some(*id);
                 let local_name = bindgen.export_interface(
                     resolve,
                     export_name,
                     *id,
                     files,
                     opts.instantiation.is_some(),
+                    flatten,
                 );
-                export_aliases.push((iface_name.to_lower_camel_case(), local_name));
+                if !flatten {
+                    export_aliases.push((iface_name.to_lower_camel_case(), local_name));
+                }
             }
             WorldItem::Type(_) => unimplemented!("type exports"),
         }
@@ -251,6 +342,31 @@ pub fn ts_bindgen(
 
     let camel = world.name.to_upper_camel_case();
 
+    if opts.tracing {
+        uwriteln!(
+            bindgen.src,
+            "export interface TraceEvent {{
+                type: 'call' | 'return' | 'error',
+                module: string,
+                function: string,
+                args?: unknown[],
+                result?: unknown,
+                error?: unknown,
+            }}"
+        );
+        if opts.instantiation.is_some() {
+            uwriteln!(
+                bindgen.import_object,
+                "$trace?: (event: TraceEvent) => void,"
+            );
+        } else {
+            uwriteln!(
+                bindgen.src,
+                "export function $setTrace(handler: ((event: TraceEvent) => void) | null): void;"
+            );
+        }
+    }
+
     // Generate a type definition for the import object to type-check
     // all imports to the component.
     //
@@ -356,6 +472,27 @@ pub fn ts_bindgen(
         None => {}
     }
 
+    if opts.instantiate_sync && opts.instantiation.is_some() {
+        uwriteln!(
+            bindgen.src,
+            "
+                /**
+                 * Synchronously instantiates this component with the provided
+                 * imports, returning a map of all the exports of the component.
+                 *
+                 * Unlike `instantiate`, no compilation takes place. The first
+                 * `compiledModules` argument must provide the already compiled
+                 * `WebAssembly.Module` of every core wasm module within the
+                 * component, keyed by its path.
+                 */
+                export function instantiateSync(
+                    compiledModules: Record<string, WebAssembly.Module>,
+                    imports: ImportObject
+                ): {camel};
+            ",
+        )
+    }
+
     files.push(&format!("{name}.d.ts"), bindgen.src.as_bytes());
     Ok(())
 }
@@ -369,7 +506,7 @@ impl TsBindgen {
         files: &mut Files,
     ) -> String {
         // in case an imported type is used as an exported type
-        let local_name = self.generate_interface(name, resolve, id, files);
+        let local_name = self.generate_interface(name, resolve, id, files, false);
         uwriteln!(
             self.import_object,
             "{}: typeof {local_name},",
@@ -389,7 +526,7 @@ impl TsBindgen {
             if iface_name == "*" {
                 uwrite!(self.import_object, "{}: ", maybe_quote_id(import_name));
                 let name = resolve.interfaces[id].name.as_ref().unwrap();
-                let local_name = self.generate_interface(name, resolve, id, files);
+                let local_name = self.generate_interface(name, resolve, id, files, false);
                 uwriteln!(self.import_object, "typeof {local_name},",);
                 return;
             }
@@ -397,7 +534,7 @@ impl TsBindgen {
         uwriteln!(self.import_object, "{}: {{", maybe_quote_id(import_name));
         for (iface_name, &id) in ifaces {
             let name = resolve.interfaces[id].name.as_ref().unwrap();
-            let local_name = self.generate_interface(name, resolve, id, files);
+            let local_name = self.generate_interface(name, resolve, id, files, false);
             uwriteln!(
                 self.import_object,
                 "{}: typeof {local_name},",
@@ -415,7 +552,9 @@ impl TsBindgen {
         _files: &mut Files,
     ) {
         uwriteln!(self.import_object, "{}: {{", maybe_quote_id(import_name));
+        let borrow_callbacks = self.borrow_callbacks;
         let mut gen = self.ts_interface(resolve, false);
+        gen.borrow_callbacks = borrow_callbacks;
         gen.ts_func(func, true, false);
         let src = gen.finish();
         self.import_object.push_str(&src);
@@ -429,9 +568,33 @@ impl TsBindgen {
         id: InterfaceId,
         files: &mut Files,
         instantiation: bool,
+        flatten: bool,
     ) -> String {
-        let local_name = self.generate_interface(export_name, resolve, id, files);
-        if instantiation {
+        let local_name = self.generate_interface(export_name, resolve, id, files, true);
+        if flatten {
+            let error_classes = resolve.interfaces[id]
+                .types
+                .iter()
+                .filter(|(_, ty)| self.error_variants.contains(ty))
+                .map(|(name, _)| name.to_upper_camel_case());
+            let members = interface_export_members(resolve, id)
+                .into_iter()
+                .chain(error_classes)
+                .collect::<Vec<_>>();
+            for member in members {
+                if instantiation {
+                    uwriteln!(
+                        self.export_object,
+                        "{member}: typeof {local_name}.{member},"
+                    );
+                } else {
+                    uwriteln!(
+                        self.export_object,
+                        "export import {member} = {local_name}.{member};"
+                    );
+                }
+            }
+        } else if instantiation {
             uwriteln!(
                 self.export_object,
                 "{}: typeof {local_name},",
@@ -459,7 +622,9 @@ impl TsBindgen {
         _files: &mut Files,
         declaration: bool,
     ) {
+        let promise_results = self.wrap_sync_in_promise;
         let mut gen = self.ts_interface(resolve, false);
+        gen.promise_results = promise_results;
         for (_, func) in funcs {
             gen.ts_func(func, false, declaration);
         }
@@ -473,6 +638,7 @@ impl TsBindgen {
         resolve: &Resolve,
         id: InterfaceId,
         files: &mut Files,
+        exported: bool,
     ) -> String {
         let iface = resolve
             .interfaces
@@ -485,22 +651,58 @@ impl TsBindgen {
         let id_name = resolve.id_of(id).unwrap_or_else(|| name.to_string());
         let goal_name = interface_goal_name(&id_name);
         let goal_name_kebab = goal_name.to_kebab_case();
-        let file_name = &format!("interfaces/{}.d.ts", goal_name_kebab);
-        let (name, iface_exists) = self.interface_names.get_or_create(file_name, &goal_name);
+        let interface_file_name = format!("interfaces/{}.d.ts", goal_name_kebab);
+        let (name, _) = self
+            .interface_names
+            .get_or_create(&interface_file_name, &goal_name);
 
         let camel = name.to_upper_camel_case();
 
+        // TypeScript doesn't work with empty namespaces, so we don't import in this case,
+        // just define them as empty.
+        let is_empty_interface = resolve.interfaces[id].functions.len() == 0
+            && resolve.interfaces[id]
+                .types
+                .iter()
+                .all(|(_, ty)| !matches!(resolve.types[*ty].kind, TypeDefKind::Resource));
+
+        let promise_results = !is_empty_interface && exported && self.wrap_sync_in_promise;
+        let borrow_callbacks = !is_empty_interface
+            && !exported
+            && self.borrow_callbacks
+            && !id_name.starts_with("wasi:");
+        // only the exports provide the error classes to match against
+        let error_classes = if exported {
+            resolve.interfaces[id]
+                .types
+                .values()
+                .filter(|ty| self.error_variants.contains(ty))
+                .copied()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let flavor = (promise_results, borrow_callbacks, !error_classes.is_empty());
+
+        // an interface that is both imported and exported is declared once,
+        // unless it is typed differently in each direction
+        let file_name = &match self.interface_flavors.get(&interface_file_name) {
+            Some(other) if *other != flavor => format!(
+                "interfaces/{}.{}.d.ts",
+                goal_name_kebab,
+                if exported { "exports" } else { "imports" }
+            ),
+            _ => interface_file_name,
+        };
+        let iface_exists = self
+            .interface_flavors
+            .insert(file_name.to_string(), flavor)
+            .is_some();
+
         let (local_name, local_exists) = self.local_names.get_or_create(file_name, &goal_name);
         let local_name = local_name.to_upper_camel_case();
 
         if !local_exists {
-            // TypeScript doesn't work with empty namespaces, so we don't import in this case,
-            // just define them as empty.
-            let is_empty_interface = resolve.interfaces[id].functions.len() == 0
-                && resolve.interfaces[id]
-                    .types
-                    .iter()
-                    .all(|(_, ty)| !matches!(resolve.types[*ty].kind, TypeDefKind::Resource));
             if is_empty_interface {
                 uwriteln!(self.src, "declare const {local_name}: {{}};");
             } else {
@@ -522,6 +724,8 @@ impl TsBindgen {
         }
 
         let mut gen = self.ts_interface(resolve, false);
+        gen.promise_results = promise_results;
+        gen.borrow_callbacks = borrow_callbacks;
 
         uwriteln!(gen.src, "export namespace {camel} {{");
         for (_, func) in resolve.interfaces[id].functions.iter() {
@@ -534,26 +738,29 @@ impl TsBindgen {
             gen.ts_func(func, false, true);
         }
         // Export resources for the interface
-        for (_, ty) in resolve.interfaces[id].types.iter() {
-            let ty = &resolve.types[*ty];
+        for (_, ty_id) in resolve.interfaces[id].types.iter() {
+            let ty = &resolve.types[*ty_id];
             if let TypeDefKind::Resource = ty.kind {
-                let resource = ty.name.as_ref().unwrap();
-                if !gen.resources.contains_key(resource) {
-                    uwriteln!(gen.src, "export {{ {} }};", resource.to_upper_camel_case());
-                    gen.resources
-                        .insert(resource.to_string(), TsInterface::new(resolve, false));
-                }
+                gen.resource_class(*ty_id);
             }
         }
+        for ty_id in error_classes {
+            gen.error_class(ty_id);
+        }
 
         uwriteln!(gen.src, "}}");
 
         gen.types(id);
         gen.post_types();
 
+        let helpers_js = std::mem::take(&mut gen.helpers_js);
         let src = gen.finish();
 
         files.push(file_name, src.as_bytes());
+        if !helpers_js.is_empty() {
+            let js_file_name = format!("{}.js", &file_name[0..file_name.len() - 5]);
+            files.push(&js_file_name, helpers_js.as_bytes());
+        }
 
         local_name
     }
@@ -567,12 +774,25 @@ impl TsBindgen {
             resolve,
             needs_ty_option: false,
             needs_ty_result: false,
+            branded_resources: self.branded_resources,
+            stability_annotations: self.stability_annotations,
+            promise_results: false,
+            variant_type_guards: self.variant_type_guards,
+            equality_helpers: self.equality_helpers,
+            variant_match_helpers: self.variant_match_helpers,
+            nullable_options: self.nullable_options,
+            abort_signals: self.abort_signals,
+            enum_case_arrays: self.enum_case_arrays,
+            borrow_callbacks: false,
+            helpers_js: Source::default(),
+            equality_helper_names: BTreeMap::new(),
+            brand: String::new(),
         }
     }
 }
 
 impl<'a> TsInterface<'a> {
-    fn new(resolve: &'a Resolve, is_root: bool) -> Self {
+    fn new(resolve: &'a Resolve, is_root: bool, nullable_options: bool) -> Self {
         TsInterface {
             is_root,
             src: Source::default(),
@@ -581,6 +801,19 @@ impl<'a> TsInterface<'a> {
             resolve,
             needs_ty_option: false,
             needs_ty_result: false,
+            branded_resources: false,
+            stability_annotations: false,
+            promise_results: false,
+            variant_type_guards: false,
+            equality_helpers: false,
+            variant_match_helpers: false,
+            nullable_options,
+            abort_signals: false,
+            enum_case_arrays: false,
+            borrow_callbacks: false,
+            helpers_js: Source::default(),
+            equality_helper_names: BTreeMap::new(),
+            brand: String::new(),
         }
     }
 
@@ -591,16 +824,74 @@ impl<'a> TsInterface<'a> {
                 "\nexport class {} {{",
                 resource.to_upper_camel_case()
             );
+            if self.branded_resources {
+                // the brand makes the class nominal, so that handles of
+                // different resource types (or structurally similar objects)
+                // are not assignable to one another. It is public, so that
+                // host implementations of imported resources can declare it.
+                uwriteln!(self.src, "readonly __brand: '{}';", source.brand);
+            }
             self.src.push_str(&source.src);
             uwriteln!(self.src, "}}")
         }
         self.src
     }
 
+    /// The class of a resource, exported from the interface when it is first used
+    fn resource_class(&mut self, id: TypeId) -> &mut TsInterface<'a> {
+        let resolve = self.resolve;
+        let ty = &resolve.types[id];
+        let resource = ty.name.as_ref().unwrap();
+        if !self.resources.contains_key(resource) {
+            uwriteln!(self.src, "export {{ {} }};", resource.to_upper_camel_case());
+            let mut class = TsInterface::new(resolve, false, self.nullable_options);
+            // same named resources of different interfaces must not share a brand
+            class.brand = match ty.owner {
+                TypeOwner::Interface(iface) => match resolve.id_of(iface) {
+                    Some(iface_id) => format!("{iface_id}#{resource}"),
+                    None => resource.to_string(),
+                },
+                _ => resource.to_string(),
+            };
+            self.resources.insert(resource.to_string(), class);
+        }
+        self.resources.get_mut(resource).unwrap()
+    }
+
+    /// The class an error variant is lifted as, with a construct signature
+    /// per case so that `instanceof` narrows to the case interfaces
+    fn error_class(&mut self, id: TypeId) {
+        let ty = &self.resolve.types[id];
+        let name = ty.name.as_ref().unwrap();
+        let TypeDefKind::Variant(variant) = &ty.kind else {
+            unreachable!()
+        };
+        let camel = name.to_upper_camel_case();
+        uwriteln!(self.src, "export const {camel}: {{");
+        for case in variant.cases.iter() {
+            let tag = &case.name;
+            let case_name = format!("{}_{}", name, case.name).to_upper_camel_case();
+            if case.ty.is_some() {
+                uwriteln!(
+                    self.src,
+                    "new (tag: '{tag}', val: {case_name}['val']): {case_name} & globalThis.Error;"
+                );
+            } else {
+                uwriteln!(
+                    self.src,
+                    "new (tag: '{tag}'): {case_name} & globalThis.Error;"
+                );
+            }
+        }
+        uwriteln!(self.src, "}};");
+    }
+
     fn docs_raw(&mut self, docs: &str) {
         self.src.push_str("/**\n");
         for line in docs.lines() {
-            self.src.push_str(&format!(" * {}\n", line));
+            // a literal `*/` in the WIT docs would otherwise end the comment early
+            self.src
+                .push_str(&format!(" * {}\n", line.replace("*/", "*\\/")));
         }
         self.src.push_str(" */\n");
     }
@@ -611,20 +902,49 @@ impl<'a> TsInterface<'a> {
         }
     }
 
+    /// Extend the docs of an item with a `@deprecated` or `@experimental` tag
+    /// for its stability, when stability annotations are enabled
+    fn stability_docs(&self, docs: &Docs, stability: &Stability) -> Docs {
+        if !self.stability_annotations {
+            return docs.clone();
+        }
+        let tag = match stability {
+            Stability::Stable {
+                deprecated: Some(version),
+                ..
+            }
+            | Stability::Unstable {
+                deprecated: Some(version),
+                ..
+            } => format!("@deprecated since version {version}"),
+            Stability::Unstable { feature, .. } => {
+                format!("@experimental requires the `{feature}` feature")
+            }
+            Stability::Stable { .. } | Stability::Unknown => return docs.clone(),
+        };
+        Docs {
+            contents: Some(match &docs.contents {
+                Some(contents) => format!("{contents}\n\n{tag}"),
+                None => tag,
+            }),
+        }
+    }
+
     fn types(&mut self, iface_id: InterfaceId) {
         let iface = &self.resolve().interfaces[iface_id];
         for (name, id) in iface.types.iter() {
             let ty = &self.resolve().types[*id];
+            let docs = &self.stability_docs(&ty.docs, &ty.stability);
             match &ty.kind {
-                TypeDefKind::Record(record) => self.type_record(*id, name, record, &ty.docs),
-                TypeDefKind::Flags(flags) => self.type_flags(*id, name, flags, &ty.docs),
-                TypeDefKind::Tuple(tuple) => self.type_tuple(*id, name, tuple, &ty.docs),
-                TypeDefKind::Enum(enum_) => self.type_enum(*id, name, enum_, &ty.docs),
-                TypeDefKind::Variant(variant) => self.type_variant(*id, name, variant, &ty.docs),
-                TypeDefKind::Option(t) => self.type_option(*id, name, t, &ty.docs),
-                TypeDefKind::Result(r) => self.type_result(*id, name, r, &ty.docs),
-                TypeDefKind::List(t) => self.type_list(*id, name, t, &ty.docs),
-                TypeDefKind::Type(t) => self.type_alias(*id, name, t, Some(iface_id), &ty.docs),
+                TypeDefKind::Record(record) => self.type_record(*id, name, record, docs),
+                TypeDefKind::Flags(flags) => self.type_flags(*id, name, flags, docs),
+                TypeDefKind::Tuple(tuple) => self.type_tuple(*id, name, tuple, docs),
+                TypeDefKind::Enum(enum_) => self.type_enum(*id, name, enum_, docs),
+                TypeDefKind::Variant(variant) => self.type_variant(*id, name, variant, docs),
+                TypeDefKind::Option(t) => self.type_option(*id, name, t, docs),
+                TypeDefKind::Result(r) => self.type_result(*id, name, r, docs),
+                TypeDefKind::List(t) => self.type_list(*id, name, t, docs),
+                TypeDefKind::Type(t) => self.type_alias(*id, name, t, Some(iface_id), docs),
                 TypeDefKind::Future(_) => todo!("generate for future"),
                 TypeDefKind::Stream(_) => todo!("generate for stream"),
                 TypeDefKind::Unknown => unreachable!(),
@@ -667,7 +987,7 @@ impl<'a> TsInterface<'a> {
                             self.src.push_str(">");
                         } else {
                             self.print_ty(t);
-                            self.src.push_str(" | undefined");
+                            self.print_none();
                         }
                     }
                     TypeDefKind::Result(r) => {
@@ -700,6 +1020,16 @@ impl<'a> TsInterface<'a> {
         }
     }
 
+    /// Print the empty value of an option that is represented without a
+    /// wrapper
+    fn print_none(&mut self) {
+        if self.nullable_options {
+            self.src.push_str(" | null");
+        } else {
+            self.src.push_str(" | undefined");
+        }
+    }
+
     fn print_optional_ty(&mut self, ty: Option<&Type>) {
         match ty {
             Some(ty) => self.print_ty(ty),
@@ -730,24 +1060,92 @@ impl<'a> TsInterface<'a> {
         self.src.push_str("]");
     }
 
+    /// Emit `match{Variant}(v, handlers)`, calling the handler of the case of
+    /// `v` with its payload. All cases must be handled, since the handler
+    /// object has a required key per case.
+    fn variant_match_helper(&mut self, name: &str, variant: &Variant) {
+        let variant_name = name.to_upper_camel_case();
+        let mut handlers = String::new();
+        let mut cases = String::new();
+        for case in variant.cases.iter() {
+            let case_name = format!("{}_{}", name, case.name).to_upper_camel_case();
+            let key = case.name.to_lower_camel_case();
+            let handler = maybe_quote_member(&key);
+            let tag = &case.name;
+            if case.ty.is_some() {
+                uwrite!(
+                    handlers,
+                    "{}: (val: {case_name}['val']) => R, ",
+                    maybe_quote_id(&key)
+                );
+                uwriteln!(cases, "case '{tag}': return handlers{handler}(v.val);");
+            } else {
+                uwrite!(handlers, "{}: () => R, ", maybe_quote_id(&key));
+                uwriteln!(cases, "case '{tag}': return handlers{handler}();");
+            }
+        }
+        uwriteln!(
+            self.src,
+            "export function match{variant_name}<R>(v: {variant_name}, handlers: {{ {handlers}}}): R;"
+        );
+        uwriteln!(
+            self.helpers_js,
+            r#"export function match{variant_name} (v, handlers) {{
+                switch (v.tag) {{
+                    {cases}default:
+                    throw new TypeError(`invalid variant tag value \`${{JSON.stringify(v.tag)}}\` specified for \`{variant_name}\``);
+                }}
+            }}"#
+        );
+    }
+
+    /// Emit `equals{Type}(a, b)`, declared next to the type and implemented in
+    /// the JS file next to the interface declaration file
+    fn equality_helper(&mut self, id: TypeId, name: &str) {
+        if !self.equality_helpers || self.is_root {
+            return;
+        }
+        let type_name = name.to_upper_camel_case();
+        let expr = equals_expr(
+            self.resolve,
+            &self.equality_helper_names,
+            &Type::Id(id),
+            "a",
+            "b",
+            0,
+        );
+        uwriteln!(
+            self.src,
+            "export function equals{type_name}(a: {type_name}, b: {type_name}): boolean;"
+        );
+        uwriteln!(
+            self.helpers_js,
+            "export function equals{type_name} (a, b) {{
+                return {expr};
+            }}"
+        );
+        self.equality_helper_names
+            .insert(id, format!("equals{type_name}"));
+    }
+
     fn ts_func(&mut self, func: &Function, default: bool, declaration: bool) {
+        let docs = self.stability_docs(&func.docs, &func.stability);
+        let promise_results = self.promise_results;
+        let abort_signal = promise_results
+            && self.abort_signals
+            && !matches!(func.kind, FunctionKind::Constructor(_));
+        let borrow_callbacks = self.borrow_callbacks;
+
         let iface = if let FunctionKind::Method(ty)
         | FunctionKind::Static(ty)
         | FunctionKind::Constructor(ty) = func.kind
         {
-            let ty = &self.resolve.types[ty];
-            let resource = ty.name.as_ref().unwrap();
-            if !self.resources.contains_key(resource) {
-                uwriteln!(self.src, "export {{ {} }};", resource.to_upper_camel_case());
-                self.resources
-                    .insert(resource.to_string(), TsInterface::new(self.resolve, false));
-            }
-            self.resources.get_mut(resource).unwrap()
+            self.resource_class(ty)
         } else {
             self
         };
 
-        iface.docs(&func.docs);
+        iface.docs(&docs);
 
         let out_name = if default {
             "default".to_string()
@@ -805,7 +1203,14 @@ impl<'a> TsInterface<'a> {
             FunctionKind::Constructor(_) => 0,
         };
 
-        for (i, (name, ty)) in func.params[param_start..].iter().enumerate() {
+        let params = &func.params[param_start..];
+        // trailing options lowered from `undefined` can be omitted by the caller
+        let optional_start = params
+            .iter()
+            .rposition(|(_, ty)| !maybe_null(iface.resolve, ty))
+            .map_or(0, |idx| idx + 1);
+
+        for (i, (name, ty)) in params.iter().enumerate() {
             if i > 0 {
                 iface.src.push_str(", ");
             }
@@ -817,8 +1222,35 @@ impl<'a> TsInterface<'a> {
                 param_name = format!("{}_", param_name);
             }
             iface.src.push_str(&param_name);
+            if i >= optional_start {
+                iface.src.push_str("?");
+            }
             iface.src.push_str(": ");
-            iface.print_ty(ty);
+            if borrow_callbacks && as_borrow(iface.resolve, ty).is_some() {
+                iface.src.push_str("<T>(callback: (borrow: ");
+                iface.print_ty(ty);
+                iface.src.push_str(") => T) => T");
+            } else {
+                iface.print_ty(ty);
+            }
+        }
+
+        if abort_signal {
+            if !params.is_empty() {
+                iface.src.push_str(", ");
+            }
+            // avoid shadowing a param that is also called `options`
+            let options_name = if params
+                .iter()
+                .any(|(name, _)| name.to_lower_camel_case() == "options")
+            {
+                "options_"
+            } else {
+                "options"
+            };
+            iface
+                .src
+                .push_str(&format!("{options_name}?: {{ signal?: AbortSignal }}"));
         }
 
         iface.src.push_str(")");
@@ -827,6 +1259,9 @@ impl<'a> TsInterface<'a> {
             return;
         }
         iface.src.push_str(": ");
+        if promise_results {
+            iface.src.push_str("Promise<");
+        }
 
         if let Some((ok_ty, _)) = func.results.throws(iface.resolve) {
             iface.print_optional_ty(ok_ty);
@@ -846,6 +1281,9 @@ impl<'a> TsInterface<'a> {
                 }
             }
         }
+        if promise_results {
+            iface.src.push_str(">");
+        }
         iface.src.push_str(format!("{}\n", end_character).as_str());
     }
 
@@ -869,7 +1307,7 @@ impl<'a> TsInterface<'a> {
         self.resolve
     }
 
-    fn type_record(&mut self, _id: TypeId, name: &str, record: &Record, docs: &Docs) {
+    fn type_record(&mut self, id: TypeId, name: &str, record: &Record, docs: &Docs) {
         self.docs(docs);
         self.src.push_str(&format!(
             "export interface {} {{\n",
@@ -885,17 +1323,24 @@ impl<'a> TsInterface<'a> {
                 option_str
             ));
             self.print_ty(ty);
+            // lifted fields are present but `null`, lowered fields may still
+            // be omitted
+            if !option_str.is_empty() && self.nullable_options {
+                self.src.push_str(" | null");
+            }
             self.src.push_str(",\n");
         }
         self.src.push_str("}\n");
+        self.equality_helper(id, name);
     }
 
-    fn type_tuple(&mut self, _id: TypeId, name: &str, tuple: &Tuple, docs: &Docs) {
+    fn type_tuple(&mut self, id: TypeId, name: &str, tuple: &Tuple, docs: &Docs) {
         self.docs(docs);
         self.src
             .push_str(&format!("export type {} = ", name.to_upper_camel_case()));
         self.print_tuple(tuple);
         self.src.push_str(";\n");
+        self.equality_helper(id, name);
     }
 
     fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
@@ -912,7 +1357,7 @@ impl<'a> TsInterface<'a> {
         self.src.push_str("}\n");
     }
 
-    fn type_variant(&mut self, _id: TypeId, name: &str, variant: &Variant, docs: &Docs) {
+    fn type_variant(&mut self, id: TypeId, name: &str, variant: &Variant, docs: &Docs) {
         self.docs(docs);
         self.src
             .push_str(&format!("export type {} = ", name.to_upper_camel_case()));
@@ -940,6 +1385,29 @@ impl<'a> TsInterface<'a> {
             }
             self.src.push_str("}\n");
         }
+        // guards are implemented in the JS file next to the interface
+        // declaration file, so the root world types do not get them
+        if self.variant_type_guards && !self.is_root {
+            let variant_name = name.to_upper_camel_case();
+            for case in variant.cases.iter() {
+                let case_name = format!("{}_{}", name, case.name).to_upper_camel_case();
+                let tag = &case.name;
+                uwriteln!(
+                    self.src,
+                    "export function is{case_name}(v: {variant_name}): v is {case_name};"
+                );
+                uwriteln!(
+                    self.helpers_js,
+                    "export function is{case_name} (v) {{
+                        return v.tag === '{tag}';
+                    }}"
+                );
+            }
+        }
+        if self.variant_match_helpers && !self.is_root {
+            self.variant_match_helper(name, variant);
+        }
+        self.equality_helper(id, name);
     }
 
     fn type_option(&mut self, _id: TypeId, name: &str, payload: &Type, docs: &Docs) {
@@ -953,7 +1421,7 @@ impl<'a> TsInterface<'a> {
             self.src.push_str(">");
         } else {
             self.print_ty(payload);
-            self.src.push_str(" | undefined");
+            self.print_none();
         }
         self.src.push_str(";\n");
     }
@@ -991,17 +1459,29 @@ impl<'a> TsInterface<'a> {
             }
         }
 
+        let name = name.to_upper_camel_case();
+        let cases = enum_
+            .cases
+            .iter()
+            .map(|case| format!("'{}'", case.name))
+            .collect::<Vec<_>>();
+
+        // derive the type from the case array, so that they can't diverge
+        if self.enum_case_arrays && !self.is_root {
+            let cases = cases.join(", ");
+            uwriteln!(self.src, "export const {name}Cases: readonly [{cases}];");
+            uwriteln!(
+                self.helpers_js,
+                "export const {name}Cases = Object.freeze([{cases}]);"
+            );
+            self.docs_raw(&complete_docs);
+            uwriteln!(self.src, "export type {name} = typeof {name}Cases[number];");
+            return;
+        }
+
         self.docs_raw(&complete_docs);
 
-        self.src
-            .push_str(&format!("export type {} = ", name.to_upper_camel_case()));
-        for (i, case) in enum_.cases.iter().enumerate() {
-            if i != 0 {
-                self.src.push_str(" | ");
-            }
-            self.src.push_str(&format!("'{}'", case.name));
-        }
-        self.src.push_str(";\n");
+        uwriteln!(self.src, "export type {name} = {};", cases.join(" | "));
     }
 
     fn type_alias(
@@ -1064,12 +1544,13 @@ impl<'a> TsInterface<'a> {
         }
     }
 
-    fn type_list(&mut self, _id: TypeId, name: &str, ty: &Type, docs: &Docs) {
+    fn type_list(&mut self, id: TypeId, name: &str, ty: &Type, docs: &Docs) {
         self.docs(docs);
         self.src
             .push_str(&format!("export type {} = ", name.to_upper_camel_case()));
         self.print_list(ty);
         self.src.push_str(";\n");
+        self.equality_helper(id, name);
     }
 }
 
@@ -1082,3 +1563,133 @@ fn interface_goal_name(iface_name: &str) -> String {
         .replace(['/', ':'], "-")
         .to_kebab_case()
 }
+
+/// JS expression comparing the lifted values `a` and `b` of type `ty` by their
+/// WIT structure.
+///
+/// Types with an equality function in `helpers` call it, others are compared
+/// inline. Resources (and streams and futures) compare by identity.
+fn equals_expr(
+    resolve: &Resolve,
+    helpers: &BTreeMap<TypeId, String>,
+    ty: &Type,
+    a: &str,
+    b: &str,
+    depth: usize,
+) -> String {
+    let id = match ty {
+        Type::Id(id) => *id,
+        // numbers, bigints and strings
+        _ => return format!("{a} === {b}"),
+    };
+    if let Some(helper) = helpers.get(&id) {
+        return format!("{helper}({a}, {b})");
+    }
+    match &resolve.types[id].kind {
+        TypeDefKind::Type(t) => equals_expr(resolve, helpers, t, a, b, depth),
+        TypeDefKind::Record(record) => all_of(record.fields.iter().map(|field| {
+            let member = maybe_quote_member(&field.name.to_lower_camel_case());
+            equals_expr(
+                resolve,
+                helpers,
+                &field.ty,
+                &format!("{a}{member}"),
+                &format!("{b}{member}"),
+                depth,
+            )
+        })),
+        TypeDefKind::Tuple(tuple) => all_of(tuple.types.iter().enumerate().map(|(i, ty)| {
+            equals_expr(
+                resolve,
+                helpers,
+                ty,
+                &format!("{a}[{i}]"),
+                &format!("{b}[{i}]"),
+                depth,
+            )
+        })),
+        // unset flags may be missing or false
+        TypeDefKind::Flags(flags) => all_of(flags.flags.iter().map(|flag| {
+            let member = maybe_quote_member(&flag.name.to_lower_camel_case());
+            format!("!{a}{member} === !{b}{member}")
+        })),
+        TypeDefKind::Variant(variant) => tagged_equals(
+            resolve,
+            helpers,
+            variant
+                .cases
+                .iter()
+                .filter_map(|case| Some((case.name.as_str(), case.ty.as_ref()?))),
+            a,
+            b,
+            depth,
+        ),
+        TypeDefKind::Result(result) => tagged_equals(
+            resolve,
+            helpers,
+            [("ok", result.ok.as_ref()), ("err", result.err.as_ref())]
+                .into_iter()
+                .filter_map(|(tag, ty)| Some((tag, ty?))),
+            a,
+            b,
+            depth,
+        ),
+        TypeDefKind::Option(t) => {
+            if maybe_null(resolve, t) {
+                tagged_equals(resolve, helpers, [("some", t)], a, b, depth)
+            } else {
+                let some = equals_expr(resolve, helpers, t, a, b, depth);
+                format!("({a} == null ? {b} == null : {b} != null && {some})")
+            }
+        }
+        TypeDefKind::List(t) => {
+            // typed arrays have `every` too
+            let (x, i) = (format!("x{depth}"), format!("i{depth}"));
+            let elem = equals_expr(resolve, helpers, t, &x, &format!("{b}[{i}]"), depth + 1);
+            format!("({a}.length === {b}.length && {a}.every(({x}, {i}) => {elem}))")
+        }
+        TypeDefKind::Enum(_)
+        | TypeDefKind::Resource
+        | TypeDefKind::Handle(_)
+        | TypeDefKind::Future(_)
+        | TypeDefKind::Stream(_) => format!("{a} === {b}"),
+        TypeDefKind::Unknown => unreachable!(),
+    }
+}
+
+/// Compare `{ tag, val }` objects, where only the given tags have a `val`
+fn tagged_equals<'a>(
+    resolve: &Resolve,
+    helpers: &BTreeMap<TypeId, String>,
+    cases: impl IntoIterator<Item = (&'a str, &'a Type)>,
+    a: &str,
+    b: &str,
+    depth: usize,
+) -> String {
+    let mut vals = String::new();
+    for (tag, ty) in cases {
+        let val = equals_expr(
+            resolve,
+            helpers,
+            ty,
+            &format!("{a}.val"),
+            &format!("{b}.val"),
+            depth,
+        );
+        uwrite!(vals, "{a}.tag === '{tag}' ? {val} : ");
+    }
+    if vals.is_empty() {
+        format!("{a}.tag === {b}.tag")
+    } else {
+        format!("({a}.tag === {b}.tag && ({vals}true))")
+    }
+}
+
+fn all_of(exprs: impl Iterator<Item = String>) -> String {
+    let exprs = exprs.collect::<Vec<_>>();
+    if exprs.is_empty() {
+        "true".to_string()
+    } else {
+        format!("({})", exprs.join(" && "))
+    }
+}
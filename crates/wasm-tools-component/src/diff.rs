@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use wit_component::DecodedWasm;
+use wit_parser::{Function, Handle, Resolve, Results, Type, TypeDefKind, TypeId, WorldItem};
+
+use crate::exports::local::wasm_tools::tools::{WitChange, WitChangeKind};
+
+/// Compare the exported WIT surface of two components.
+///
+/// Exports are identified by their export name, and interface members by
+/// `{interface}#{member}`. Members of interfaces that were added or removed
+/// as a whole are not listed separately.
+pub fn component_diff(old: &[u8], new: &[u8]) -> Result<Vec<WitChange>, String> {
+    let old = export_surface(old)?;
+    let new = export_surface(new)?;
+
+    let in_both = |name: &str| match name.split_once('#') {
+        Some((iface, _)) => old.contains_key(iface) && new.contains_key(iface),
+        None => true,
+    };
+
+    let mut changes = Vec::new();
+    for (name, before) in &old {
+        if !in_both(name) {
+            continue;
+        }
+        match new.get(name) {
+            None => changes.push(WitChange {
+                kind: WitChangeKind::Removed,
+                name: name.to_string(),
+                before: Some(before.to_string()),
+                after: None,
+            }),
+            Some(after) if after != before => changes.push(WitChange {
+                kind: WitChangeKind::Changed,
+                name: name.to_string(),
+                before: Some(before.to_string()),
+                after: Some(after.to_string()),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (name, after) in &new {
+        if in_both(name) && !old.contains_key(name) {
+            changes.push(WitChange {
+                kind: WitChangeKind::Added,
+                name: name.to_string(),
+                before: None,
+                after: Some(after.to_string()),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(changes)
+}
+
+/// The WIT signature of every export of the component and of every member
+/// of its exported interfaces, by name
+fn export_surface(binary: &[u8]) -> Result<BTreeMap<String, String>, String> {
+    let decoded = wit_component::decode(binary)
+        .map_err(|e| format!("Failed to decode wit component\n{:?}", e))?;
+    let (resolve, world) = match &decoded {
+        DecodedWasm::WitPackage(_, _) => return Err("Expected a component".to_string()),
+        DecodedWasm::Component(resolve, world) => (resolve, *world),
+    };
+
+    let mut surface = BTreeMap::new();
+    for (key, item) in &resolve.worlds[world].exports {
+        let name = resolve.name_world_key(key);
+        match item {
+            WorldItem::Function(func) => {
+                surface.insert(name, func_signature(resolve, func));
+            }
+            WorldItem::Type(id) => {
+                surface.insert(name, type_definition(resolve, *id));
+            }
+            WorldItem::Interface { id, .. } => {
+                let iface = &resolve.interfaces[*id];
+                for (ty_name, id) in &iface.types {
+                    surface.insert(format!("{name}#{ty_name}"), type_definition(resolve, *id));
+                }
+                for (func_name, func) in &iface.functions {
+                    surface.insert(format!("{name}#{func_name}"), func_signature(resolve, func));
+                }
+                surface.insert(name, "interface".to_string());
+            }
+        }
+    }
+    Ok(surface)
+}
+
+fn func_signature(resolve: &Resolve, func: &Function) -> String {
+    let params = func
+        .params
+        .iter()
+        .map(|(name, ty)| format!("{name}: {}", type_name(resolve, ty)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let results = match &func.results {
+        Results::Named(results) if results.is_empty() => String::new(),
+        Results::Named(results) => format!(
+            " -> ({})",
+            results
+                .iter()
+                .map(|(name, ty)| format!("{name}: {}", type_name(resolve, ty)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Results::Anon(ty) => format!(" -> {}", type_name(resolve, ty)),
+    };
+    format!("func({params}){results}")
+}
+
+fn type_definition(resolve: &Resolve, id: TypeId) -> String {
+    let (kind, items) = match &resolve.types[id].kind {
+        TypeDefKind::Record(record) => (
+            "record",
+            record
+                .fields
+                .iter()
+                .map(|field| format!("{}: {}", field.name, type_name(resolve, &field.ty)))
+                .collect::<Vec<_>>(),
+        ),
+        TypeDefKind::Variant(variant) => (
+            "variant",
+            variant
+                .cases
+                .iter()
+                .map(|case| match &case.ty {
+                    Some(ty) => format!("{}({})", case.name, type_name(resolve, ty)),
+                    None => case.name.to_string(),
+                })
+                .collect(),
+        ),
+        TypeDefKind::Enum(enum_) => (
+            "enum",
+            enum_
+                .cases
+                .iter()
+                .map(|case| case.name.to_string())
+                .collect(),
+        ),
+        TypeDefKind::Flags(flags) => (
+            "flags",
+            flags
+                .flags
+                .iter()
+                .map(|flag| flag.name.to_string())
+                .collect(),
+        ),
+        TypeDefKind::Resource => return "resource".to_string(),
+        // aliases, including `use`d types, and named anonymous types
+        TypeDefKind::Type(ty) => return format!("type = {}", type_name(resolve, ty)),
+        _ => return format!("type = {}", anonymous_type_name(resolve, id)),
+    };
+    format!("{kind} {{ {} }}", items.join(", "))
+}
+
+fn type_name(resolve: &Resolve, ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::S8 => "s8".to_string(),
+        Type::S16 => "s16".to_string(),
+        Type::S32 => "s32".to_string(),
+        Type::S64 => "s64".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "string".to_string(),
+        Type::Id(id) => match &resolve.types[*id].name {
+            Some(name) => name.to_string(),
+            None => anonymous_type_name(resolve, *id),
+        },
+    }
+}
+
+fn anonymous_type_name(resolve: &Resolve, id: TypeId) -> String {
+    let optional = |ty: &Option<Type>| match ty {
+        Some(ty) => type_name(resolve, ty),
+        None => "_".to_string(),
+    };
+    match &resolve.types[id].kind {
+        TypeDefKind::List(ty) => format!("list<{}>", type_name(resolve, ty)),
+        TypeDefKind::Option(ty) => format!("option<{}>", type_name(resolve, ty)),
+        TypeDefKind::Result(result) => format!(
+            "result<{}, {}>",
+            optional(&result.ok),
+            optional(&result.err)
+        ),
+        TypeDefKind::Tuple(tuple) => format!(
+            "tuple<{}>",
+            tuple
+                .types
+                .iter()
+                .map(|ty| type_name(resolve, ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TypeDefKind::Handle(Handle::Own(id)) => type_name(resolve, &Type::Id(*id)),
+        TypeDefKind::Handle(Handle::Borrow(id)) => {
+            format!("borrow<{}>", type_name(resolve, &Type::Id(*id)))
+        }
+        TypeDefKind::Future(ty) => format!("future<{}>", optional(ty)),
+        TypeDefKind::Stream(stream) => format!(
+            "stream<{}, {}>",
+            optional(&stream.element),
+            optional(&stream.end)
+        ),
+        TypeDefKind::Type(ty) => type_name(resolve, ty),
+        _ => "unknown".to_string(),
+    }
+}
@@ -8,9 +8,11 @@ use wit_parser::Resolve;
 
 use exports::local::wasm_tools::tools::{
     EmbedOpts, EnabledFeatureSet, Guest, ModuleMetaType, ModuleMetadata, ProducersFields,
-    StringEncoding,
+    StringEncoding, WitChange,
 };
 
+mod diff;
+
 wit_bindgen::generate!({
     world: "wasm-tools"
 });
@@ -70,6 +72,10 @@ impl Guest for WasmToolsJs {
         Ok(output)
     }
 
+    fn component_diff(old: Vec<u8>, new: Vec<u8>) -> Result<Vec<WitChange>, String> {
+        diff::component_diff(&old, &new)
+    }
+
     fn component_embed(embed_opts: EmbedOpts) -> Result<Vec<u8>, String> {
         let binary = &embed_opts.binary;
 
@@ -11,7 +11,7 @@ export namespace WasiHttpOutgoingHandler {
    * or not allowed to be made. Otherwise, protocol errors are reported
    * through the `future-incoming-response`.
    */
-  export function handle(request: OutgoingRequest, options: RequestOptions | undefined): FutureIncomingResponse;
+  export function handle(request: OutgoingRequest, options?: RequestOptions | undefined): FutureIncomingResponse;
 }
 import type { OutgoingRequest } from './wasi-http-types.js';
 export { OutgoingRequest };
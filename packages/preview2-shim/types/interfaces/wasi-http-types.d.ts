@@ -546,7 +546,7 @@ export class OutgoingBody {
   * to the body (via `write`) does not match the value given in the
   * Content-Length.
   */
-  static finish(this_: OutgoingBody, trailers: Trailers | undefined): void;
+  static finish(this_: OutgoingBody, trailers?: Trailers | undefined): void;
 }
 
 export class OutgoingRequest {
@@ -591,7 +591,7 @@ export class OutgoingRequest {
   * When `none`, this represents an empty Path and empty Query. Fails is the
   * string given is not a syntactically valid path and query uri component.
   */
-  setPathWithQuery(pathWithQuery: string | undefined): void;
+  setPathWithQuery(pathWithQuery?: string | undefined): void;
   /**
   * Get the HTTP Related Scheme for the Request. When `none`, the
   * implementation may choose an appropriate default scheme.
@@ -602,7 +602,7 @@ export class OutgoingRequest {
   * implementation may choose an appropriate default scheme. Fails if the
   * string given is not a syntactically valid uri scheme.
   */
-  setScheme(scheme: Scheme | undefined): void;
+  setScheme(scheme?: Scheme | undefined): void;
   /**
   * Get the authority of the Request's target URI. A value of `none` may be used
   * with Related Schemes which do not require an authority. The HTTP and
@@ -615,7 +615,7 @@ export class OutgoingRequest {
   * HTTPS schemes always require an authority. Fails if the string given is
   * not a syntactically valid URI authority.
   */
-  setAuthority(authority: string | undefined): void;
+  setAuthority(authority?: string | undefined): void;
   /**
   * Get the headers associated with the Request.
   * 
@@ -681,7 +681,7 @@ export class RequestOptions {
   * Set the timeout for the initial connect to the HTTP Server. An error
   * return value indicates that this timeout is not supported.
   */
-  setConnectTimeout(duration: Duration | undefined): void;
+  setConnectTimeout(duration?: Duration | undefined): void;
   /**
   * The timeout for receiving the first byte of the Response body.
   */
@@ -690,7 +690,7 @@ export class RequestOptions {
   * Set the timeout for receiving the first byte of the Response body. An
   * error return value indicates that this timeout is not supported.
   */
-  setFirstByteTimeout(duration: Duration | undefined): void;
+  setFirstByteTimeout(duration?: Duration | undefined): void;
   /**
   * The timeout for receiving subsequent chunks of bytes in the Response
   * body stream.
@@ -701,7 +701,7 @@ export class RequestOptions {
   * body stream. An error return value indicates that this timeout is not
   * supported.
   */
-  setBetweenBytesTimeout(duration: Duration | undefined): void;
+  setBetweenBytesTimeout(duration?: Duration | undefined): void;
 }
 
 export class ResponseOutparam {
@@ -222,7 +222,7 @@ export class UdpSocket {
       * - <https://learn.microsoft.com/en-us/windows/win32/api/winsock2/nf-winsock2-connect>
       * - <https://man.freebsd.org/cgi/man.cgi?connect>
       */
-      stream(remoteAddress: IpSocketAddress | undefined): [IncomingDatagramStream, OutgoingDatagramStream];
+      stream(remoteAddress?: IpSocketAddress | undefined): [IncomingDatagramStream, OutgoingDatagramStream];
       /**
       * Get the current bound address.
       * 
@@ -1,7 +1,7 @@
 export { optimizeComponent as opt } from './cmd/opt.js';
 export { transpileComponent as transpile, typesComponent as types } from './cmd/transpile.js';
 import { $init, tools } from "../obj/wasm-tools.js";
-const { print: printFn, parse: parseFn, componentWit: componentWitFn, componentNew: componentNewFn, componentEmbed: componentEmbedFn, metadataAdd: metadataAddFn, metadataShow: metadataShowFn } = tools;
+const { print: printFn, parse: parseFn, componentWit: componentWitFn, componentNew: componentNewFn, componentEmbed: componentEmbedFn, metadataAdd: metadataAddFn, metadataShow: metadataShowFn, componentDiff: componentDiffFn } = tools;
 
 /**
  * @param {Parameters<import('../obj/wasm-tools.js').print>[0]} binary
@@ -61,6 +61,15 @@ export async function metadataShow (binary) {
   await $init;
   return metadataShowFn(binary);
 }
+/**
+ * @param {Parameters<import('../obj/wasm-tools.js').componentDiff>[0]} oldBinary
+ * @param {Parameters<import('../obj/wasm-tools.js').componentDiff>[1]} newBinary
+ * @return {Promise<ReturnType<import('../obj/wasm-tools.js').componentDiff>>}
+ */
+export async function componentDiff (oldBinary, newBinary) {
+  await $init;
+  return componentDiffFn(oldBinary, newBinary);
+}
 export function preview1AdapterCommandPath () {
   return new URL('../lib/wasi_snapshot_preview1.command.wasm', import.meta.url);
 }
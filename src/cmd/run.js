@@ -7,6 +7,10 @@ import process from 'node:process';
 import { fileURLToPath, pathToFileURL } from 'node:url';
 import c from 'chalk-template';
 
+// Exit code used when the component traps, matching Wasmtime
+// (128 + SIGABRT on Unix, 3 on Windows)
+const TRAP_EXIT_CODE = process.platform === 'win32' ? 3 : 134;
+
 export async function run (componentPath, args, opts) {
   // Ensure that `args` is an array
   args = [...args];
@@ -23,7 +27,8 @@ export async function run (componentPath, args, opts) {
     }
     catch (e) {
       console.error(e);
-      process.exit(1);
+      // distinguish traps from the component returning an error
+      process.exit(e instanceof WebAssembly.RuntimeError ? ${TRAP_EXIT_CODE} : 1);
     }
   `);
 }
@@ -60,6 +65,28 @@ export async function serve (componentPath, args, opts) {
 async function runComponent (componentPath, args, opts, executor) {
   const jcoImport = opts.jcoImport ? resolve(opts.jcoImport) : null;
 
+  // the guest environment is the host environment plus any --jco-env overrides
+  const env = { ...process.env };
+  for (const mapping of opts.jcoEnv || []) {
+    const eqIdx = mapping.indexOf('=');
+    if (eqIdx === -1)
+      throw c`Invalid {bold --jco-env} value "${mapping}", expected {italic key=value}.`;
+    env[mapping.slice(0, eqIdx)] = mapping.slice(eqIdx + 1);
+  }
+
+  // --jco-preopen host::guest replaces the default preopens, with the guest
+  // path defaulting to the host path
+  let preopens = null;
+  for (const mapping of opts.jcoPreopen || []) {
+    preopens = preopens || {};
+    const sepIdx = mapping.indexOf('::');
+    const hostPath = sepIdx === -1 ? mapping : mapping.slice(0, sepIdx);
+    const guestPath = sepIdx === -1 ? mapping : mapping.slice(sepIdx + 2);
+    if (!hostPath || !guestPath)
+      throw c`Invalid {bold --jco-preopen} value "${mapping}", expected {italic host::guest} or {italic host}.`;
+    preopens[guestPath] = resolve(hostPath);
+  }
+
   const name = basename(componentPath.slice(0, -extname(componentPath).length || Infinity));
   const outDir = opts.jcoDir || await getTmpDir();
   if (opts.jcoDir) {
@@ -105,6 +132,8 @@ async function runComponent (componentPath, args, opts, executor) {
     const runPath = resolve(outDir, '_run.js');
     await writeFile(runPath, `
       ${jcoImport ? `import ${JSON.stringify(pathToFileURL(jcoImport))}` : ''}
+      ${preopens ? `import { _setPreopens } from '@bytecodealliance/preview2-shim/filesystem';
+      _setPreopens(${JSON.stringify(preopens)});` : ''}
       import process from 'node:process';
       try {
         process.argv[1] = "${name}";
@@ -116,10 +145,11 @@ async function runComponent (componentPath, args, opts, executor) {
     const nodePath = process.env.JCO_RUN_PATH || process.argv[0];
 
     process.exitCode = await new Promise((resolve, reject) => {
-      const cp = spawn(nodePath, [...process.env.JCO_RUN_ARGS ? process.env.JCO_RUN_ARGS.split(' ') : [], runPath, ...args], { stdio: 'inherit' });
+      const cp = spawn(nodePath, [...process.env.JCO_RUN_ARGS ? process.env.JCO_RUN_ARGS.split(' ') : [], runPath, ...args], { stdio: 'inherit', env });
 
       cp.on('error', reject);
-      cp.on('exit', resolve);
+      // a child killed by a signal has no exit code
+      cp.on('exit', code => resolve(code ?? 1));
     });
   }
   finally {
@@ -1,4 +1,4 @@
-import { $init, generate, generateTypes } from '../../obj/js-component-bindgen-component.js';
+import { $init, generate, generateTo, generateTypes } from '../../obj/js-component-bindgen-component.js';
 import { writeFile } from 'node:fs/promises';
 import { mkdir } from 'node:fs/promises';
 import { dirname, extname, basename, resolve } from 'node:path';
@@ -11,6 +11,7 @@ import { $init as wasmToolsInit, tools } from "../../obj/wasm-tools.js";
 const { componentEmbed, componentNew } = tools;
 import ora from '#ora';
 import { platform } from 'node:process';
+import { createHash } from 'node:crypto';
 
 const isWindows = platform === 'win32';
 
@@ -26,6 +27,19 @@ export async function types (witPath, opts) {
  *   worldName?: string,
  *   instantiation?: 'async' | 'sync',
  *   tlaCompat?: bool,
+ *   brandedResources?: bool,
+ *   stabilityAnnotations?: bool,
+ *   instantiateSync?: bool,
+ *   errorsExtendError?: bool,
+ *   wrapSyncInPromise?: bool,
+ *   variantTypeGuards?: bool,
+ *   equalityHelpers?: bool,
+ *   variantMatchHelpers?: bool,
+ *   flattenExports?: bool,
+ *   nullableOptions?: bool,
+ *   abortSignals?: bool,
+ *   enumCaseArrays?: bool,
+ *   borrowCallbacks?: bool,
  *   outDir?: string,
  *   features?: string[] | 'all',
  * }} opts
@@ -57,6 +71,19 @@ export async function typesComponent (witPath, opts) {
     tlaCompat: opts.tlaCompat ?? false,
     world: opts.worldName,
     features,
+    brandedResources: opts.brandedResources ?? false,
+    stabilityAnnotations: opts.stabilityAnnotations ?? false,
+    instantiateSync: opts.instantiateSync ?? false,
+    errorsExtendError: opts.errorsExtendError ?? false,
+    wrapSyncInPromise: opts.wrapSyncInPromise ?? false,
+    variantTypeGuards: opts.variantTypeGuards ?? false,
+    equalityHelpers: opts.equalityHelpers ?? false,
+    variantMatchHelpers: opts.variantMatchHelpers ?? false,
+    flattenExports: opts.flattenExports ?? false,
+    nullableOptions: opts.nullableOptions ?? false,
+    abortSignals: opts.abortSignals ?? false,
+    enumCaseArrays: opts.enumCaseArrays ?? false,
+    borrowCallbacks: opts.borrowCallbacks ?? false,
   }).map(([name, file]) => [`${outDir}${name}`, file]));
 }
 
@@ -65,14 +92,21 @@ async function writeFiles(files, summaryTitle) {
     await mkdir(dirname(name), { recursive: true });
     await writeFile(name, file);
   }));
-  if (!summaryTitle)
-    return;
+  if (summaryTitle)
+    printSummary(Object.entries(files).map(([name, source]) => [name, source.length]), summaryTitle);
+}
+
+/**
+ * @param {[string, number][]} sizes
+ * @param {string} summaryTitle
+ */
+function printSummary(sizes, summaryTitle) {
   console.log(c`
   {bold ${summaryTitle}:}
 
-${table(Object.entries(files).map(([name, source]) => [
+${table(sizes.map(([name, size]) => [
     c` - {italic ${name}}  `,
-    c`{black.italic ${sizeStr(source.length)}}`
+    c`{black.italic ${sizeStr(size)}}`
   ]))}`);
 }
 
@@ -98,8 +132,45 @@ export async function transpile (componentPath, opts, program) {
     opts.name = basename(componentPath.slice(0, -extname(componentPath).length || Infinity));
   if (opts.map)
     opts.map = Object.fromEntries(opts.map.map(mapping => mapping.split('=')));
-  const { files } = await transpileComponent(component, opts);
+  // without any post-processing of the generated JS, each file is written out
+  // as soon as it is generated instead of being held in memory
+  if (!opts.js && !opts.minify) {
+    const { files, hash } = await transpileComponentToDir(component, opts);
+    if (!opts.quiet)
+      printSummary(files, 'Transpiled JS Component Files');
+    if (opts.printHash)
+      console.log(hash);
+    return;
+  }
+  const { files, hash } = await transpileComponent(component, opts);
   await writeFiles(files, opts.quiet ? false : 'Transpiled JS Component Files');
+  if (opts.printHash)
+    console.log(hash);
+}
+
+/**
+ * Transpile a component straight into `opts.outDir`, which must not need the
+ * `--js` or `--minify` post-processing
+ *
+ * @param {Uint8Array} component
+ * @param {Parameters<typeof transpileComponent>[1]} opts
+ * @returns {Promise<{ files: [string, number][], imports: string[], exports: [string, 'function' | 'instance'][], hash: string }>}
+ */
+async function transpileComponentToDir (component, opts) {
+  const prepared = await prepareComponent(component, opts);
+  const { files, imports, exports } = generateTo(prepared.component, prepared.options,
+    (isWindows ? '//?/' : '') + resolve(opts.outDir));
+
+  let outDir = opts.outDir.replace(/\\/g, '/');
+  if (!outDir.endsWith('/'))
+    outDir += '/';
+  const written = files.map(([name, size]) => [`${outDir}${name}`, Number(size)]);
+  let hash;
+  if (opts.printHash) {
+    const contents = await Promise.all(written.map(async ([name]) => [name, await readFile(name)]));
+    hash = outputHash(contents, outDir);
+  }
+  return { files: written, imports, exports, hash };
 }
 
 /**
@@ -119,34 +190,16 @@ async function wasm2Js (source) {
 }
 
 /**
+ * Initialize the bindgen, optimize the component if requested, and build the
+ * options for generating its bindings
  *
  * @param {Uint8Array} component
- * @param {{
- *   name: string,
- *   instantiation?: 'async' | 'sync',
- *   importBindings?: 'js' | 'optimized' | 'hybrid' | 'direct-optimized',
- *   map?: Record<string, string>,
- *   validLiftingOptimization?: bool,
- *   tracing?: bool,
- *   nodejsCompat?: bool,
- *   tlaCompat?: bool,
- *   base64Cutoff?: bool,
- *   js?: bool,
- *   minify?: bool,
- *   optimize?: bool,
- *   namespacedExports?: bool,
- *   outDir?: string,
- *   multiMemory?: bool,
- *   experimentalIdlImports?: bool,
- *   optArgs?: string[],
- * }} opts
- * @returns {Promise<{ files: { [filename: string]: Uint8Array }, imports: string[], exports: [string, 'function' | 'instance'][] }>}
+ * @param {Parameters<typeof transpileComponent>[1]} opts
  */
-export async function transpileComponent (component, opts = {}) {
+async function prepareComponent (component, opts) {
   await $init;
   if (opts.instantiation) opts.wasiShim = false;
 
-  let spinner;
   const showSpinner = getShowSpinner();
   if (opts.optimize) {
     if (showSpinner) setShowSpinner(true);
@@ -176,7 +229,7 @@ export async function transpileComponent (component, opts = {}) {
     instantiation = { tag: 'async' };
   }
 
-  let { files, imports, exports } = generate(component, {
+  const options = {
     name: opts.name ?? 'component',
     map: Object.entries(opts.map ?? {}),
     instantiation,
@@ -189,15 +242,79 @@ export async function transpileComponent (component, opts = {}) {
     base64Cutoff: opts.js ? 0 : opts.base64Cutoff ?? 5000,
     noNamespacedExports: opts.namespacedExports === false,
     multiMemory: opts.multiMemory === true,
+    brandedResources: opts.brandedResources === true,
+    stabilityAnnotations: opts.stabilityAnnotations === true,
+    // the --js instantiate wrapper replaces the instantiate function it would call
+    instantiateSync: opts.instantiateSync === true && !opts.js,
+    errorsExtendError: opts.errorsExtendError === true,
+    wrapSyncInPromise: opts.wrapSyncInPromise === true,
+    variantTypeGuards: opts.variantTypeGuards === true,
+    equalityHelpers: opts.equalityHelpers === true,
+    variantMatchHelpers: opts.variantMatchHelpers === true,
+    flattenExports: opts.flattenExports === true,
+    runtimeValidation: opts.runtimeValidation === true,
+    nullableOptions: opts.nullableOptions === true,
+    abortSignals: opts.abortSignals === true,
+    enumCaseArrays: opts.enumCaseArrays === true,
+    borrowCallbacks: opts.borrowCallbacks === true,
+    manifest: opts.manifest === true,
     idlImports: opts.experimentalIdlImports === true,
-  });
+  };
+
+  return { component, options, instantiation, showSpinner };
+}
+
+/**
+ *
+ * @param {Uint8Array} component
+ * @param {{
+ *   name: string,
+ *   instantiation?: 'async' | 'sync',
+ *   importBindings?: 'js' | 'optimized' | 'hybrid' | 'direct-optimized',
+ *   map?: Record<string, string>,
+ *   validLiftingOptimization?: bool,
+ *   tracing?: bool,
+ *   nodejsCompat?: bool,
+ *   tlaCompat?: bool,
+ *   base64Cutoff?: bool,
+ *   js?: bool,
+ *   minify?: bool,
+ *   optimize?: bool,
+ *   namespacedExports?: bool,
+ *   outDir?: string,
+ *   multiMemory?: bool,
+ *   brandedResources?: bool,
+ *   stabilityAnnotations?: bool,
+ *   instantiateSync?: bool,
+ *   errorsExtendError?: bool,
+ *   wrapSyncInPromise?: bool,
+ *   variantTypeGuards?: bool,
+ *   equalityHelpers?: bool,
+ *   variantMatchHelpers?: bool,
+ *   flattenExports?: bool,
+ *   runtimeValidation?: bool,
+ *   nullableOptions?: bool,
+ *   abortSignals?: bool,
+ *   enumCaseArrays?: bool,
+ *   borrowCallbacks?: bool,
+ *   manifest?: bool,
+ *   experimentalIdlImports?: bool,
+ *   optArgs?: string[],
+ * }} opts
+ * @returns {Promise<{ files: { [filename: string]: Uint8Array }, imports: string[], exports: [string, 'function' | 'instance'][], hash: string }>}
+ */
+export async function transpileComponent (component, opts = {}) {
+  const prepared = await prepareComponent(component, opts);
+  const { instantiation, showSpinner } = prepared;
+  let { files, imports, exports } = generate(prepared.component, prepared.options);
+  let spinner;
 
   let outDir = (opts.outDir ?? '').replace(/\\/g, '/');
   if (!outDir.endsWith('/') && outDir !== '')
     outDir += '/';
   files = files.map(([name, source]) => [`${outDir}${name}`, source]);
 
-  const jsFile = files.find(([name]) => name.endsWith('.js'));
+  const jsFile = files.find(([name]) => name === `${outDir}${opts.name ?? 'component'}.js`);
 
   // Generate code for the `--js` option.
   //
@@ -431,7 +548,28 @@ ${autoInstantiate}`;
     }));
   }
 
-  return { files: Object.fromEntries(files), imports, exports };
+  return { files: Object.fromEntries(files), imports, exports, hash: outputHash(files, outDir) };
+}
+
+/**
+ * Stable SHA-256 hex digest over all output files, independent of the
+ * output directory and of the order the files were generated in
+ *
+ * @param {[string, Uint8Array][]} files
+ * @param {string} outDir
+ * @returns {string}
+ */
+function outputHash (files, outDir) {
+  const hash = createHash('sha256');
+  const sorted = files
+    .map(([name, source]) => [name.slice(outDir.length), source])
+    .sort(([a], [b]) => a < b ? -1 : a > b ? 1 : 0);
+  for (const [name, source] of sorted) {
+    // length-prefix each entry so that file boundaries are unambiguous
+    hash.update(`${name}\0${source.length}\0`);
+    hash.update(source);
+  }
+  return hash.digest('hex');
 }
 
 // emscripten asm mangles specifiers to be valid identifiers
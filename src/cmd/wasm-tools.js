@@ -1,7 +1,7 @@
 import { writeFile } from "node:fs/promises";
 import { readFile, isWindows } from '../common.js';
 import { $init, tools } from "../../obj/wasm-tools.js";
-const { print: printFn, parse: parseFn, componentWit: componentWitFn, componentNew: componentNewFn, componentEmbed: componentEmbedFn, metadataAdd: metadataAddFn, metadataShow: metadataShowFn } = tools;
+const { print: printFn, parse: parseFn, componentWit: componentWitFn, componentNew: componentNewFn, componentEmbed: componentEmbedFn, metadataAdd: metadataAddFn, metadataShow: metadataShowFn, componentDiff: componentDiffFn } = tools;
 import { resolve, basename, extname } from 'node:path';
 import c from 'chalk-template';
 
@@ -34,6 +34,26 @@ export async function componentWit(file, opts) {
   }
 }
 
+export async function componentDiff(oldFile, newFile) {
+  await $init;
+  const changes = componentDiffFn(await readFile(oldFile), await readFile(newFile));
+  let output = '';
+  for (const { kind, name, before, after } of changes) {
+    if (kind === 'added')
+      output += c`{green + ${name}}: ${after}\n`;
+    else if (kind === 'removed')
+      output += c`{red - ${name}}: ${before}\n`;
+    else
+      output += c`{yellow ~ ${name}}: ${before} -> ${after}\n`;
+  }
+  if (changes.length === 0)
+    output += 'No changes to the exported WIT.\n';
+  process.stdout.write(output);
+  // removals and signature changes break existing users of the component
+  if (changes.some(({ kind }) => kind !== 'added'))
+    process.exitCode = 1;
+}
+
 export async function componentNew(file, opts) {
   await $init;
   const source = file ? await readFile(file) : null;
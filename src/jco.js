@@ -3,7 +3,7 @@ import { program, Option } from 'commander';
 import { opt } from './cmd/opt.js';
 import { transpile, types } from './cmd/transpile.js';
 import { run as runCmd, serve as serveCmd } from './cmd/run.js';
-import { parse, print, componentNew, componentEmbed, metadataAdd, metadataShow, componentWit } from './cmd/wasm-tools.js';
+import { parse, print, componentNew, componentEmbed, metadataAdd, metadataShow, componentWit, componentDiff } from './cmd/wasm-tools.js';
 import { componentize } from './cmd/componentize.js';
 import c from 'chalk-template';
 
@@ -60,9 +60,25 @@ program.command('transpile')
   .option('--stub', 'generate a stub implementation from a WIT file directly')
   .option('--js', 'output JS instead of core WebAssembly')
   .addOption(new Option('-I, --instantiation [mode]', 'output for custom module instantiation').choices(['async', 'sync']).preset('async'))
+  .option('--instantiate-sync', 'with --instantiation, also output an instantiateSync function taking precompiled core modules')
   .option('-q, --quiet', 'disable output summary')
+  .option('--print-hash', 'print a SHA-256 hash of all output files, for checking build reproducibility')
   .option('--no-namespaced-exports', 'disable namespaced exports for typescript compatibility')
   .option('--multi-memory', 'optimized output for Wasm multi-memory')
+  .option('--branded-resources', 'emit nominally typed TypeScript resource classes')
+  .option('--stability-annotations', 'annotate deprecated and unstable items with JSDoc tags')
+  .option('--errors-extend-error', 'lift error variants into classes extending Error')
+  .option('--wrap-sync-in-promise', 'make all exported functions async, returning promises')
+  .option('--variant-type-guards', 'output an is{Variant}{Case} type guard function for each variant case')
+  .option('--equality-helpers', 'output an equals{Type} structural equality function for each record, variant, tuple and list type')
+  .option('--variant-match-helpers', 'output a match{Variant} function dispatching to a handler per variant case')
+  .option('--flatten-exports', 'export the members of the single exported interface at the top level')
+  .option('--runtime-validation', 'check lowered values against their WIT types at runtime')
+  .option('--nullable-options', 'lift option none values as null instead of undefined')
+  .option('--abort-signals', 'accept a trailing { signal } argument to cancel --wrap-sync-in-promise exports')
+  .option('--enum-case-arrays', 'output a {Enum}Cases array of the case names of each enum')
+  .option('--borrow-callbacks', 'pass borrowed resources to imports as callbacks that stop working after the call')
+  .option('--manifest', 'output a [name].manifest.json describing the component imports and exports')
   .option('--', 'for --optimize, custom wasm-opt arguments (defaults to best size optimization)')
   .action(asyncAction(transpile));
 
@@ -75,14 +91,27 @@ program.command('types')
   .requiredOption('-o, --out-dir <out-dir>', 'output directory')
   .option('--tla-compat', 'generates types for the TLA compat output with an async $init promise export')
   .addOption(new Option('-I, --instantiation [mode]', 'type output for custom module instantiation').choices(['async', 'sync']).preset('async'))
+  .option('--instantiate-sync', 'with --instantiation, also output types for the instantiateSync function')
+  .option('--errors-extend-error', 'declare the classes extending Error that exported error variants are lifted as')
+  .option('--wrap-sync-in-promise', 'type all exported functions as returning promises')
   .option('-q, --quiet', 'disable output summary')
   .option('--feature <feature>', 'enable one specific WIT feature (repeatable)', collectOptions, [])
   .option('--all-features', 'enable all features')
+  .option('--branded-resources', 'emit nominally typed TypeScript resource classes')
+  .option('--stability-annotations', 'annotate deprecated and unstable items with JSDoc tags')
+  .option('--variant-type-guards', 'output an is{Variant}{Case} type guard function for each variant case')
+  .option('--equality-helpers', 'output an equals{Type} structural equality function for each record, variant, tuple and list type')
+  .option('--variant-match-helpers', 'output a match{Variant} function dispatching to a handler per variant case')
+  .option('--flatten-exports', 'type the members of the single exported interface as top level exports')
+  .option('--nullable-options', 'type option none values as null instead of undefined')
+  .option('--abort-signals', 'type a trailing { signal } argument on --wrap-sync-in-promise exports')
+  .option('--enum-case-arrays', 'output a {Enum}Cases array of the case names of each enum')
+  .option('--borrow-callbacks', 'type borrowed resources passed to imports as callbacks')
   .action(asyncAction(types));
 
 program.command('run')
   .description('Run a WASI Command component')
-  .usage('<command.wasm> <args...>')
+  .usage('<command.wasm> [-- <args...>]')
   .helpOption(false)
   .allowUnknownOption(true)
   .allowExcessArguments(true)
@@ -90,6 +119,8 @@ program.command('run')
   .option('--jco-dir <dir>', 'Instead of using a temporary dir, set the output directory for the run command')
   .option('--jco-trace', 'Enable call tracing')
   .option('--jco-import <module>', 'Custom module to import before the run executes to support custom environment setup')
+  .option('--jco-env <key=value>', 'environment variable to set for the component (repeatable)', collectOptions, [])
+  .option('--jco-preopen <host::guest>', 'host directory to preopen for the component, at the given guest path (repeatable)', collectOptions, [])
   .option('--jco-map <mappings...>', 'specifier=./output custom mappings for the component imports')
   .addOption(new Option('--jco-import-bindings [mode]', 'bindings mode for imports').choices(['js', 'optimized', 'hybrid', 'direct-optimized']).preset('js'))
   .argument('[args...]', 'Any CLI arguments for the component')
@@ -114,6 +145,8 @@ program.command('serve')
   .option('--jco-dir <dir>', 'Instead of using a temporary dir, set the output directory for the transpiled code')
   .option('--jco-trace', 'Enable call tracing')
   .option('--jco-import <module>', 'Custom module to import before the server executes to support custom environment setup')
+  .option('--jco-env <key=value>', 'environment variable to set for the component (repeatable)', collectOptions, [])
+  .option('--jco-preopen <host::guest>', 'host directory to preopen for the component, at the given guest path (repeatable)', collectOptions, [])
   .addOption(new Option('--jco-import-bindings [mode]', 'bindings mode for imports').choices(['js', 'optimized', 'hybrid', 'direct-optimized']).preset('js'))
   .option('--jco-map <mappings...>', 'specifier=./output custom mappings for the component imports')
   .argument('[args...]', 'Any CLI arguments for the component')
@@ -142,6 +175,13 @@ program.command('wit')
   .option('-o, --output <output-file>', 'WIT output file path')
   .action(asyncAction(componentWit));
 
+program.command('diff')
+  .description('compare the exported WIT of two WebAssembly Components, failing on breaking changes')
+  .usage('<old-component> <new-component>')
+  .argument('<old-component>', 'Wasm component binary filepath of the previous version')
+  .argument('<new-component>', 'Wasm component binary filepath of the new version')
+  .action(asyncAction(componentDiff));
+
 program.command('print')
   .description('print the WebAssembly WAT text for a binary file [wasm-tools print]')
   .argument('<input>', 'input file to process')
@@ -30,6 +30,20 @@ export async function apiTest(fixtures) {
       ok(files[name + ".js"]);
     });
 
+    test("Transpile output hash", async () => {
+      const name = "flavorful";
+      const component = await readFile(
+        `test/fixtures/components/${name}.component.wasm`
+      );
+      const { hash } = await transpile(component, { name });
+      ok(/^[0-9a-f]{64}$/.test(hash));
+      // the hash is deterministic and independent of the output directory
+      strictEqual((await transpile(component, { name })).hash, hash);
+      strictEqual((await transpile(component, { name, outDir: "out" })).hash, hash);
+      // but reflects changes to the output
+      ok((await transpile(component, { name, tracing: true })).hash !== hash);
+    });
+
     test("Transpile & Optimize & Minify", async () => {
       const name = "flavorful";
       const component = await readFile(
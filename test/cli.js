@@ -1,7 +1,7 @@
 import { resolve, normalize, sep } from "node:path";
 import { execArgv } from "node:process";
 import { tmpdir, EOL } from "node:os";
-import { deepStrictEqual, ok, strictEqual } from "node:assert";
+import { deepStrictEqual, ok, rejects, strictEqual } from "node:assert";
 import {
   mkdir,
   readFile,
@@ -172,6 +172,64 @@ export async function cliTest(fixtures) {
       );
     });
 
+    test("Transpile with flatten exports", async () => {
+      const name = "flavorful";
+      const { stderr } = await exec(
+        jcoPath,
+        "transpile",
+        `test/fixtures/components/${name}.component.wasm`,
+        "--name",
+        name,
+        "--map",
+        "testwasi=./wasi.js",
+        "--flatten-exports",
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      const source = await readFile(`${outDir}/${name}.js`, "utf8");
+      ok(source.includes("listOfVariants$1 as listOfVariants,"));
+      ok(!source.includes("as 'test:flavorful/test'"));
+      const dts = await readFile(`${outDir}/${name}.d.ts`, "utf8");
+      ok(dts.includes("export import listOfVariants = TestFlavorfulTest.listOfVariants;"));
+
+      // only a single exported interface can be flattened
+      await rejects(
+        exec(
+          jcoPath,
+          "transpile",
+          `test/fixtures/components/strings.component.wasm`,
+          "--flatten-exports",
+          "-o",
+          outDir
+        ),
+        /flattening exports requires the component to export exactly one interface/
+      );
+    });
+
+    test("Transpile with wildcard map captures", async () => {
+      const { stderr } = await exec(
+        jcoPath,
+        "transpile",
+        `test/fixtures/env-allow.composed.wasm`,
+        "--no-wasi-shim",
+        "--name",
+        "env-allow",
+        "--map",
+        "wasi:*/*=./shims/#1.js##2",
+        "--map",
+        "wasi:cli/*=./cli.js#*",
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      const source = await readFile(`${outDir}/env-allow.js`, "utf8");
+      ok(source.includes("import { preopens, types } from './shims/filesystem.js';"));
+      ok(source.includes("import { error, streams } from './shims/io.js';"));
+      // the more specific mapping takes precedence
+      ok(source.includes("import { environment, stderr, stdin, stdout } from './cli.js';"));
+    });
+
     test("Type generation", async () => {
       const { stderr } = await exec(
         jcoPath,
@@ -245,6 +303,65 @@ export async function cliTest(fixtures) {
       ok(source.includes("export function c(): void;"));
     });
 
+    test("Type generation (stability annotations)", async () => {
+      const { stderr } = await exec(
+        jcoPath,
+        "types",
+        "test/fixtures/wits/feature-gates-deprecated.wit",
+        "--all-features",
+        "--stability-annotations",
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      const source = await readFile(`${outDir}/interfaces/test-feature-gates-deprecated-foo.d.ts`, "utf8");
+      ok(source.includes("export namespace TestFeatureGatesDeprecatedFoo {\n  export function a(): void;"));
+      ok(source.includes("   * Superseded by `a`\n   * \n   * @deprecated since version 0.2.0\n   */\n  export function b(): void;"));
+      ok(source.includes("   * @experimental requires the `enable-c` feature\n   */\n  export function c(): void;"));
+    });
+
+    test("Type generation (branded resources)", async () => {
+      const { stderr } = await exec(
+        jcoPath,
+        "types",
+        "test/fixtures/wits/resource-naming/resource-naming.wit",
+        "--branded-resources",
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      const source = await readFile(
+        `${outDir}/interfaces/test-test-resource-import-and-export.d.ts`,
+        "utf8"
+      );
+      ok(source.includes("export class Thing {\n  readonly __brand: 'test:test/resource-import-and-export#thing';"));
+    });
+
+    test("Type generation (helpers)", async () => {
+      const { stderr } = await exec(
+        jcoPath,
+        "types",
+        "test/fixtures/wits/variant-guards/variant-guards.wit",
+        "--variant-type-guards",
+        "--variant-match-helpers",
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      const source = await readFile(
+        `${outDir}/interfaces/test-variant-guards-shapes.d.ts`,
+        "utf8"
+      );
+      ok(source.includes("export function isShapeCircle(v: Shape): v is ShapeCircle;"));
+      ok(source.includes("export function matchFill<R>(v: Fill, handlers: { none: () => R, solid: (val: FillSolid['val']) => R, }): R;"));
+      // the helpers are implemented alongside their declarations
+      const js = await readFile(
+        `${outDir}/interfaces/test-variant-guards-shapes.js`,
+        "utf8"
+      );
+      ok(js.includes("export function isShapeCircle("));
+    });
+
     test("TypeScript naming checks", async () => {
       const { stderr } = await exec(
         jcoPath,
@@ -293,6 +410,28 @@ export async function cliTest(fixtures) {
       ok(source.includes("export {\n  $init"));
     });
 
+    test("Transpile to JS with sync instantiation", async () => {
+      const name = "flavorful";
+      const { stderr } = await exec(
+        jcoPath,
+        "transpile",
+        `test/fixtures/components/${name}.component.wasm`,
+        "--name",
+        name,
+        "--instantiation",
+        "sync",
+        "--js",
+        "--base64-cutoff=0",
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      const source = await readFile(`${outDir}/${name}.js`, "utf8");
+      ok(source.includes("FUNCTION_TABLE"));
+      ok(source.includes("export function instantiate(imports) {"));
+      ok(!source.includes("async function instantiate"));
+    });
+
     test("Transpile without namespaced exports", async () => {
       const name = "flavorful";
       const { stderr } = await exec(
@@ -336,6 +475,106 @@ export async function cliTest(fixtures) {
       ok(finalLine.toString().includes("test as 'test:flavorful/test'"));
     });
 
+    test("Transpile with errors extending Error", async () => {
+      const name = "results";
+      const { stderr } = await exec(
+        jcoPath,
+        "transpile",
+        `test/fixtures/components/${name}.component.wasm`,
+        "--errors-extend-error",
+        "--no-wasi-shim",
+        "--name",
+        name,
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      const source = await readFile(`${outDir}/${name}.js`, "utf8");
+      ok(source.includes("class E3 extends Error {"));
+      ok(source.includes("new E3('e1', "));
+      ok(source.includes("E3: E3,"));
+      // the class is only declared on the exported interface
+      const imports = await readFile(`${outDir}/interfaces/test-results-test.d.ts`, "utf8");
+      ok(!imports.includes("export const E3"));
+      const exports = await readFile(`${outDir}/interfaces/test-results-test.exports.d.ts`, "utf8");
+      ok(exports.includes("export const E3: {\n    new (tag: 'e1', val: E3E1['val']): E3E1 & globalThis.Error;"));
+
+      // error resources with a to-string method are rejected
+      await rejects(
+        exec(
+          jcoPath,
+          "types",
+          "test/fixtures/wits/error-resource/error-resource.wit",
+          "--errors-extend-error",
+          "-o",
+          outDir
+        ),
+        /errors extending Error are not supported for the error resource `test:error-resource\/files#error`/
+      );
+    });
+
+    test("Transpile with wrap sync in promise", async () => {
+      const name = "results";
+      const { stderr } = await exec(
+        jcoPath,
+        "transpile",
+        `test/fixtures/components/${name}.component.wasm`,
+        "--wrap-sync-in-promise",
+        "--no-wasi-shim",
+        "--name",
+        name,
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      // the interface is both imported and exported, and only its exports
+      // return promises
+      const imports = await readFile(`${outDir}/interfaces/test-results-test.d.ts`, "utf8");
+      ok(imports.includes("export function stringError(a: number): number;"));
+      const exports = await readFile(`${outDir}/interfaces/test-results-test.exports.d.ts`, "utf8");
+      ok(exports.includes("export function stringError(a: number): Promise<number>;"));
+      const dts = await readFile(`${outDir}/${name}.d.ts`, "utf8");
+      ok(dts.includes("import { TestResultsTest as TestResultsTest1 } from './interfaces/test-results-test.exports.js';"));
+      ok(dts.includes("export const test: typeof TestResultsTest1;"));
+    });
+
+    test("Transpile with manifest", async () => {
+      const name = "flavorful";
+      const { stderr } = await exec(
+        jcoPath,
+        "transpile",
+        `test/fixtures/components/${name}.component.wasm`,
+        "--manifest",
+        "--name",
+        name,
+        "-o",
+        outDir
+      );
+      strictEqual(stderr, "");
+      const manifest = JSON.parse(
+        await readFile(`${outDir}/${name}.manifest.json`, "utf8")
+      );
+      const stderrImport = manifest.imports.find(
+        (impt) => impt.name === "wasi:cli/stderr@0.2.0"
+      );
+      strictEqual(stderrImport.kind, "interface");
+      strictEqual(stderrImport.specifier, "@bytecodealliance/preview2-shim/cli");
+      strictEqual(stderrImport.js, "stderr");
+      ok(
+        stderrImport.items.some(
+          (item) => item.kind === "function" && item.js === "getStderr"
+        )
+      );
+      const testExport = manifest.exports.find(
+        (expt) => expt.name === "test:flavorful/test"
+      );
+      strictEqual(testExport.kind, "interface");
+      strictEqual(testExport.js, "test");
+      // entries are sorted by name
+      const importNames = manifest.imports.map((impt) => impt.name);
+      strictEqual(importNames.join(), [...importNames].sort().join());
+    });
+
     test("Optimize", async () => {
       const component = await readFile(
         `test/fixtures/components/flavorful.component.wasm`
@@ -471,6 +710,78 @@ export async function cliTest(fixtures) {
       }
     });
 
+    test("Diff", async () => {
+      const components = {};
+      for (const version of ["old", "new", "additive", "changed"]) {
+        components[version] = resolve(tmpDir, `diff-${version}.wasm`);
+        await exec(
+          jcoPath,
+          "embed",
+          "--dummy",
+          "--wit",
+          `test/fixtures/wits/diff/${version}.wit`,
+          "-o",
+          components[version]
+        );
+        await exec(
+          jcoPath,
+          "new",
+          components[version],
+          "-o",
+          components[version]
+        );
+      }
+
+      // identical components have no changes
+      {
+        const { stdout } = await exec(
+          jcoPath,
+          "diff",
+          components.old,
+          components.old
+        );
+        strictEqual(stdout, "No changes to the exported WIT.\n");
+      }
+
+      // the removed function fails the diff
+      await rejects(
+        exec(jcoPath, "diff", components.old, components.new),
+        (err) => {
+          const lines = err.message.trim().split("\n");
+          deepStrictEqual(lines, [
+            "+ test:diff/api#added: func() -> list<point>",
+            "- test:diff/api#removed: func(s: string)",
+          ]);
+          return true;
+        }
+      );
+
+      // additions alone are compatible
+      {
+        const { stdout } = await exec(
+          jcoPath,
+          "diff",
+          components.old,
+          components.additive
+        );
+        deepStrictEqual(stdout.trim().split("\n"), [
+          "+ test:diff/api#added: func() -> list<point>",
+        ]);
+      }
+
+      // a changed signature fails the diff
+      await rejects(
+        exec(jcoPath, "diff", components.old, components.changed),
+        (err) => {
+          strictEqual(err.exitCode, 1);
+          deepStrictEqual(err.message.trim().split("\n"), [
+            "~ test:diff/api#distance: func(a: point, b: point) -> f64 -> func(a: point, b: point) -> f32",
+          ]);
+          return true;
+        }
+      );
+    });
+
     test("Component new adapt", async () => {
       const { stderr } = await exec(
         jcoPath,
@@ -0,0 +1,44 @@
+(component
+  (core module (;0;)
+    (type (;0;) (func (param i32 i32 i32) (result i32)))
+    (func (;0;) (type 0) (param i32 i32 i32) (result i32)
+      local.get 0
+      local.get 2
+      i32.const 0
+      local.get 1
+      select
+      i32.add
+    )
+    (export "test:record-options/test#end" (func 0))
+    (@producers
+      (processed-by "wit-component" "0.217.0")
+    )
+  )
+  (core instance (;0;) (instantiate 0))
+  (type (;0;) (option u32))
+  (type (;1;) (record (field "start" u32) (field "len" 0)))
+  (type (;2;) (func (param "r" 1) (result u32)))
+  (alias core export 0 "test:record-options/test#end" (core func (;0;)))
+  (func (;0;) (type 2) (canon lift (core func 0)))
+  (component (;0;)
+    (type (;0;) (option u32))
+    (type (;1;) (record (field "start" u32) (field "len" 0)))
+    (import "import-type-range" (type (;2;) (eq 1)))
+    (type (;3;) (func (param "r" 2) (result u32)))
+    (import "import-func-end" (func (;0;) (type 3)))
+    (type (;4;) (option u32))
+    (type (;5;) (record (field "start" u32) (field "len" 4)))
+    (export (;6;) "range" (type 5))
+    (type (;7;) (func (param "r" 6) (result u32)))
+    (export (;1;) "end" (func 0) (func (type 7)))
+  )
+  (instance (;0;) (instantiate 0
+      (with "import-func-end" (func 0))
+      (with "import-type-range" (type 1))
+    )
+  )
+  (export (;1;) "test:record-options/test" (instance 0))
+  (@producers
+    (processed-by "wit-component" "0.217.0")
+  )
+)
@@ -0,0 +1,38 @@
+;; A command printing each of its environment variables on a line
+(module
+  (import "wasi_snapshot_preview1" "environ_sizes_get"
+    (func $environ_sizes_get (param i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "environ_get"
+    (func $environ_get (param i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write"
+    (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 4)
+  (func (export "_start")
+    (local $i i32)
+    (local $end i32)
+    ;; the entry pointers go at 1024 and the entries themselves at 65536
+    (drop (call $environ_sizes_get (i32.const 0) (i32.const 4)))
+    (drop (call $environ_get (i32.const 1024) (i32.const 65536)))
+    (local.set $end (i32.add (i32.const 65536) (i32.load (i32.const 4))))
+
+    ;; replace the NUL terminating each entry with a newline
+    (local.set $i (i32.const 65536))
+    (block $terminated
+      (loop $terminate
+        (br_if $terminated (i32.ge_u (local.get $i) (local.get $end)))
+        (if (i32.eqz (i32.load8_u (local.get $i)))
+          (then (i32.store8 (local.get $i) (i32.const 10))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $terminate)))
+
+    ;; write them all to stdout, which may take several writes
+    (local.set $i (i32.const 65536))
+    (block $written
+      (loop $write
+        (br_if $written (i32.ge_u (local.get $i) (local.get $end)))
+        (i32.store (i32.const 8) (local.get $i))
+        (i32.store (i32.const 12) (i32.sub (local.get $end) (local.get $i)))
+        (br_if $written
+          (call $fd_write (i32.const 1) (i32.const 8) (i32.const 1) (i32.const 16)))
+        (local.set $i (i32.add (local.get $i) (i32.load (i32.const 16))))
+        (br $write)))))
@@ -0,0 +1,5 @@
+;; A command whose entry point traps
+(module
+  (memory (export "memory") 1)
+  (func (export "_start")
+    unreachable))
@@ -0,0 +1,7 @@
+// Used with `jco run --jco-import` to report the guest preopen paths
+import process from 'node:process';
+import { preopens } from '@bytecodealliance/preview2-shim/filesystem';
+
+process.on('exit', () => {
+  console.error(`preopens: ${preopens.getDirectories().map(([, guestPath]) => guestPath).join(', ')}`);
+});
@@ -20,9 +20,12 @@ export async function exec(cmd, ...args) {
       stderr += chunk;
     });
     cp.on("error", reject);
-    cp.on("exit", (code) =>
-      code === 0 ? resolve() : reject(new Error((stderr || stdout).toString()))
-    );
+    cp.on("exit", (code) => {
+      if (code === 0) return resolve();
+      const err = new Error((stderr || stdout).toString());
+      err.exitCode = code;
+      reject(err);
+    });
   });
   return { stdout, stderr };
 }
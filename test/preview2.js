@@ -1,12 +1,13 @@
-import { strictEqual } from "node:assert";
+import { deepStrictEqual, rejects, strictEqual } from "node:assert";
 import { readFile, rm, writeFile } from "node:fs/promises";
 import { createServer } from "node:http";
 import { resolve } from "node:path";
+import { env, platform } from "node:process";
 
 import { fileURLToPath, pathToFileURL } from "url";
 import { HTTPServer } from "@bytecodealliance/preview2-shim/http";
 
-import { componentNew, preview1AdapterCommandPath } from "../src/api.js";
+import { componentNew, parse, preview1AdapterCommandPath } from "../src/api.js";
 import { exec, jcoPath, getTmpDir } from "./helpers.js";
 
 export async function preview2Test() {
@@ -53,6 +54,109 @@ export async function preview2Test() {
       strictEqual(stderr, "writing to stderr: hello, world\n");
     });
 
+    test("run trap exit code", async () => {
+      const component = await parse(
+        await readFile(`test/fixtures/modules/trap.wat`, "utf8")
+      );
+      const generatedComponent = await componentNew(component, [
+        [
+          "wasi_snapshot_preview1",
+          await readFile(preview1AdapterCommandPath()),
+        ],
+      ]);
+      await writeFile(outFile, generatedComponent);
+
+      await rejects(exec(jcoPath, "run", outFile), {
+        exitCode: platform === "win32" ? 3 : 134,
+      });
+    });
+
+    test("run --jco-preopen", async () => {
+      const component = await readFile(
+        `test/fixtures/modules/hello_stdout.wasm`
+      );
+      const generatedComponent = await componentNew(component, [
+        [
+          "wasi_snapshot_preview1",
+          await readFile(preview1AdapterCommandPath()),
+        ],
+      ]);
+      await writeFile(outFile, generatedComponent);
+
+      const printPreopens = "test/fixtures/run/print-preopens.js";
+      {
+        const { stderr } = await exec(
+          jcoPath,
+          "run",
+          "--jco-import",
+          printPreopens,
+          outFile
+        );
+        strictEqual(stderr, "writing to stderr: hello, world\npreopens: /\n");
+      }
+      {
+        const { stdout, stderr } = await exec(
+          jcoPath,
+          "run",
+          "--jco-import",
+          printPreopens,
+          "--jco-preopen",
+          "test/fixtures::/fixtures",
+          "--jco-preopen",
+          tmpDir,
+          outFile
+        );
+        strictEqual(stdout, "writing to stdout: hello, world\n");
+        strictEqual(
+          stderr,
+          `writing to stderr: hello, world\npreopens: /fixtures, ${tmpDir}\n`
+        );
+      }
+    });
+
+    test("run --jco-env", async () => {
+      const component = await parse(
+        await readFile(`test/fixtures/modules/print_env.wat`, "utf8")
+      );
+      const generatedComponent = await componentNew(component, [
+        [
+          "wasi_snapshot_preview1",
+          await readFile(preview1AdapterCommandPath()),
+        ],
+      ]);
+      await writeFile(outFile, generatedComponent);
+
+      // the guest sees the inherited environment, with --jco-env on top
+      env.JCO_TEST_INHERITED = "inherited";
+      env.JCO_TEST_OVERRIDDEN = "inherited";
+      try {
+        const { stdout, stderr } = await exec(
+          jcoPath,
+          "run",
+          "--jco-env",
+          "JCO_TEST_PASSED=passed",
+          "--jco-env",
+          "JCO_TEST_OVERRIDDEN=a=b",
+          outFile
+        );
+        strictEqual(stderr, "");
+        deepStrictEqual(
+          stdout
+            .split("\n")
+            .filter((line) => line.startsWith("JCO_TEST_"))
+            .sort(),
+          [
+            "JCO_TEST_INHERITED=inherited",
+            "JCO_TEST_OVERRIDDEN=a=b",
+            "JCO_TEST_PASSED=passed",
+          ]
+        );
+      } finally {
+        delete env.JCO_TEST_INHERITED;
+        delete env.JCO_TEST_OVERRIDDEN;
+      }
+    });
+
     test("wasi-http-proxy", async () => {
       const server = createServer(async (req, res) => {
         if (req.url == "/api/examples") {
@@ -1,5 +1,5 @@
 import { strictEqual } from 'node:assert';
-import { existsSync } from 'node:fs';
+import { readdirSync } from 'node:fs';
 import { exec } from './helpers.js';
 import { tsGenerationPromise } from './typescript.js';
 
@@ -7,16 +7,18 @@ export async function runtimeTest (fixtures) {
   suite('Runtime', async () => {
     for (const fixture of fixtures) {
       if (fixture.startsWith('dummy_') || fixture.startsWith('wasi-http-proxy')) continue;
-      const runtimeName = fixture.replace(/(\.component)?\.(wat|wasm)$/, '');
-      if (!existsSync(`test/runtime/${runtimeName}.ts`))
-        continue;
-      test(runtimeName, async () => {
-        try {
-          await tsGenerationPromise();
-        } catch {}
-        const { stderr } = await exec(`test/output/${runtimeName}.js`);
-        strictEqual(stderr, '');
-      });
+      const name = fixture.replace(/(\.component)?\.(wat|wasm)$/, '');
+      // the unflagged test and its flagged variants, e.g. `strings.sync.ts`
+      for (const testFile of readdirSync('test/runtime/').filter(testFile => testFile.startsWith(`${name}.`))) {
+        const runtimeName = testFile.replace(/\.ts$/, '');
+        test(runtimeName, async () => {
+          try {
+            await tsGenerationPromise();
+          } catch {}
+          const { stderr } = await exec(`test/output/${runtimeName}.js`);
+          strictEqual(stderr, '');
+        });
+      }
     }
   });
 }
@@ -0,0 +1,41 @@
+// Flags: --borrow-callbacks --map example2:component/backend=../example_guest_import.borrow-callbacks.js
+
+import { strictEqual, throws } from 'node:assert';
+
+let instance = 0;
+
+export class Scalars {
+  instance: number;
+  constructor () {
+    this.instance = instance++;
+  }
+  getB () {
+    return this.instance;
+  }
+}
+
+type WithBorrow = <T>(callback: (borrow: Scalars) => T) => T;
+
+let received: WithBorrow[] = [];
+export function fetch (withScalar: WithBorrow) {
+  received.push(withScalar);
+  strictEqual(withScalar(scalar => scalar instanceof Scalars), true);
+  return new Scalars();
+}
+
+async function run() {
+  const wasm = await import('../output/example_guest_import.borrow-callbacks/example_guest_import.borrow-callbacks.js');
+
+  const x = new Scalars();
+  strictEqual(wasm.front.handle(x), 1);
+
+  // the borrow can no longer be used once fetch has returned
+  strictEqual(received.length, 1);
+  throws(() => received[0](scalar => scalar.getB()), {
+    name: 'TypeError',
+    message: 'Resource error: borrowed "Scalars" resource used after the call it was passed to returned',
+  });
+}
+
+// Async cycle handling
+setTimeout(run);
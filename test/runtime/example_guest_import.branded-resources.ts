@@ -0,0 +1,37 @@
+// Flags: --branded-resources --map example2:component/backend=../example_guest_import.branded-resources.js
+
+import type { Example2ComponentBackend, Scalars as ImportedScalars } from '../output/example_guest_import.branded-resources/interfaces/example2-component-backend.js';
+
+import { strictEqual } from 'node:assert';
+
+let instance = 0;
+
+// a host implementation of the branded imported resource, which declares the
+// brand to be assignable to the generated class
+export class Scalars {
+  declare readonly __brand: 'example2:component/backend@0.1.0#scalars';
+  instance: number;
+  constructor () {
+    this.instance = instance++;
+  }
+  getB () {
+    return this.instance;
+  }
+}
+
+export function fetch (_scalar: ImportedScalars) {
+  return new Scalars();
+}
+
+// type checks the host implementation against the generated imports
+const backend: typeof Example2ComponentBackend = { Scalars, fetch };
+
+async function run() {
+  const wasm = await import('../output/example_guest_import.branded-resources/example_guest_import.branded-resources.js');
+
+  strictEqual(backend.Scalars, Scalars);
+  strictEqual(wasm.front.handle(new Scalars()), 1);
+}
+
+// Async cycle handling
+setTimeout(run);
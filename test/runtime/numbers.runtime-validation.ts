@@ -0,0 +1,60 @@
+// Flags: --instantiation --runtime-validation
+
+import * as helpers from "./helpers.js";
+import { instantiate } from "../output/numbers.runtime-validation/numbers.runtime-validation.js";
+import { strictEqual, throws } from 'node:assert';
+
+async function run() {
+  let scalar = 0;
+  // @ts-ignore
+  const wasm = await instantiate(helpers.loadWasm, {
+    ...helpers.wasi,
+    'test:numbers/test': {
+      roundtripU8(x) { return x; },
+      roundtripS8(x) { return x; },
+      roundtripU16(x) { return x; },
+      roundtripS16(x) { return x; },
+      roundtripU32(x) { return x; },
+      roundtripS32(x) { return x; },
+      roundtripU64(x) { return x; },
+      roundtripS64(x) { return x; },
+      roundtripF32(x) { return x; },
+      roundtripF64(x) { return x; },
+      roundtripChar(x) { return x; },
+      setScalar(x) { scalar = x; },
+      getScalar() { return scalar; },
+    }
+  });
+
+  wasm.testImports();
+
+  // values in range are lowered as before
+  strictEqual(wasm.test.roundtripU8((1 << 8) - 1), (1 << 8) - 1);
+  strictEqual(wasm.test.roundtripS8(-(1 << 7)), -(1 << 7));
+  strictEqual(wasm.test.roundtripU32(~0 >>> 0), ~0 >>> 0);
+  strictEqual(wasm.test.roundtripS32(1 << 31), 1 << 31);
+  strictEqual(wasm.test.roundtripU64((1n << 64n) - 1n), (1n << 64n) - 1n);
+  strictEqual(wasm.test.roundtripS64(-(1n << 63n)), -(1n << 63n));
+
+  // out of range and non-integer values throw instead of wrapping
+  throws(() => wasm.test.roundtripU8(1 << 8), {
+    name: 'TypeError',
+    message: 'test:numbers/test#roundtrip-u8: invalid u8 value 256, expected an integer from 0 to 255',
+  });
+  throws(() => wasm.test.roundtripU8(-1), /invalid u8 value -1/);
+  throws(() => wasm.test.roundtripS8(1 << 7), /test:numbers\/test#roundtrip-s8: invalid s8 value 128/);
+  throws(() => wasm.test.roundtripU16(1.5), /invalid u16 value 1\.5/);
+  throws(() => wasm.test.roundtripS16(-(1 << 15) - 1), /invalid s16 value -32769/);
+  throws(() => wasm.test.roundtripU32(2 ** 32), /invalid u32 value 4294967296/);
+  throws(() => wasm.test.roundtripS32(2 ** 31), /invalid s32 value 2147483648/);
+  throws(() => wasm.test.roundtripU64(1n << 64n), /invalid u64 value 18446744073709551616/);
+  throws(() => wasm.test.roundtripS64(-(1n << 63n) - 1n), /invalid s64 value -9223372036854775809/);
+  // @ts-ignore
+  throws(() => wasm.test.roundtripU64(1), /invalid u64 value 1,/);
+
+  wasm.test.setScalar(2);
+  strictEqual(wasm.test.getScalar(), 2);
+  throws(() => wasm.test.setScalar(-1), /test:numbers\/test#set-scalar: invalid u32 value -1/);
+}
+
+await run()
@@ -0,0 +1,27 @@
+// Flags: --instantiation --runtime-validation
+
+import * as helpers from "./helpers.js";
+import { instantiate } from "../output/record-options.runtime-validation/record-options.runtime-validation.js";
+import { strictEqual, throws } from 'node:assert';
+
+async function run() {
+  // @ts-ignore
+  const wasm = await instantiate(helpers.loadWasm, helpers.wasi);
+
+  strictEqual(wasm.test.end({ start: 1, len: 2 }), 3);
+  // optional fields may be left out
+  strictEqual(wasm.test.end({ start: 1 }), 1);
+
+  // @ts-ignore
+  throws(() => wasm.test.end({ len: 2 }), {
+    name: 'TypeError',
+    message: 'test:record-options/test#end: record range is missing the required field "start"',
+  });
+  // @ts-ignore
+  throws(() => wasm.test.end(null), {
+    name: 'TypeError',
+    message: 'test:record-options/test#end: expected record range, got null',
+  });
+}
+
+await run()
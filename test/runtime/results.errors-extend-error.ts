@@ -0,0 +1,78 @@
+// Flags: --instantiation --errors-extend-error
+
+import * as helpers from './helpers.js';
+import { instantiate } from '../output/results.errors-extend-error/results.errors-extend-error.js';
+
+// @ts-ignore
+import * as assert from 'assert';
+
+function errorPayload(fn: () => unknown): unknown {
+  try {
+    fn();
+  } catch (e) {
+    return (e as { payload: unknown }).payload;
+  }
+  throw new Error('expected an error');
+}
+
+async function run() {
+  let thrown: unknown;
+  // @ts-ignore
+  const wasm = await instantiate(helpers.loadWasm, {
+    ...helpers.wasi,
+    'wasi:cli/environment': {
+      getEnvironment() { return []; },
+    },
+    'wasi:cli/exit': {
+      exit() {},
+    },
+    'test:results/test': {
+      stringError(a) { return a; },
+      enumError(a) { return a; },
+      recordError(a) { return a; },
+      variantError(a) {
+        if (a == 0)
+          throw { tag: 'e1', val: 'b' };
+        if (a == 1)
+          throw { tag: 'e2', val: { line: 3, column: 4 } };
+        if (a == 3)
+          throw thrown;
+        return a;
+      },
+      emptyError(a) { return a; },
+      doubleError(a) { return { tag: 'ok', val: undefined }; },
+    },
+  });
+
+  assert.strictEqual(wasm.test.variantError(2), 2);
+
+  const e1 = errorPayload(() => wasm.test.variantError(0));
+  assert.ok(e1 instanceof Error);
+  if (!(e1 instanceof wasm.test.E3))
+    throw new Error('expected an E3 error');
+  assert.strictEqual(e1.name, 'E3');
+  assert.strictEqual(e1.message, 'e1: b');
+  assert.ok(e1.stack?.includes('variantError'));
+  assert.strictEqual(e1.tag, 'e1');
+  assert.strictEqual(e1.val, 'b');
+
+  const e2 = errorPayload(() => wasm.test.variantError(1));
+  if (!(e2 instanceof wasm.test.E3))
+    throw new Error('expected an E3 error');
+  // narrowed to the case by the tag
+  if (e2.tag !== 'e2')
+    throw new Error('expected the e2 case');
+  assert.strictEqual(e2.message, 'e2');
+  assert.deepStrictEqual(e2.val, { line: 3, column: 4 });
+
+  // the error classes lower as the variant
+  thrown = new wasm.test.E3('e1', 'c');
+  const e3 = errorPayload(() => wasm.test.variantError(3));
+  assert.notStrictEqual(e3, thrown);
+  if (!(e3 instanceof wasm.test.E3))
+    throw new Error('expected an E3 error');
+  assert.strictEqual(e3.tag, 'e1');
+  assert.strictEqual(e3.val, 'c');
+}
+
+await run()
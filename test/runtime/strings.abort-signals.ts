@@ -0,0 +1,43 @@
+// Flags: --instantiation --wrap-sync-in-promise --abort-signals
+
+import * as helpers from './helpers.js';
+import { instantiate } from '../output/strings.abort-signals/strings.abort-signals.js';
+
+// @ts-ignore
+import * as assert from 'assert';
+
+async function run() {
+  let importController: AbortController | null = null;
+  // @ts-ignore
+  const wasm = await instantiate(helpers.loadWasm, {
+    ...helpers.wasi,
+    'test:strings/imports': {
+      takeBasic(s: string) {
+        assert.strictEqual(s, 'latin utf16');
+        importController?.abort();
+      },
+      returnUnicode() {
+        return '🚀🚀🚀 𠈄𓀀';
+      }
+    }
+  });
+
+  // the options argument is optional
+  assert.strictEqual(await wasm.roundtrip('str'), 'str');
+  assert.strictEqual(await wasm.roundtrip('str', {}), 'str');
+  assert.strictEqual(await wasm.roundtrip('str', { signal: new AbortController().signal }), 'str');
+
+  // aborted before the call
+  const controller = new AbortController();
+  controller.abort();
+  await assert.rejects(wasm.roundtrip('str', { signal: controller.signal }), { name: 'AbortError' });
+  await assert.rejects(wasm.roundtrip('str', { signal: AbortSignal.abort('stop') }), reason => reason === 'stop');
+
+  // aborted during the call
+  importController = new AbortController();
+  await assert.rejects(wasm.testImports({ signal: importController.signal }), { name: 'AbortError' });
+  importController = null;
+  await wasm.testImports();
+}
+
+await run()
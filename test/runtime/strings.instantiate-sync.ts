@@ -0,0 +1,36 @@
+// Flags: --instantiation async --instantiate-sync
+
+import * as helpers from './helpers.js';
+import { instantiateSync } from '../output/strings.instantiate-sync/strings.instantiate-sync.js';
+
+// @ts-ignore
+import * as assert from 'assert';
+// @ts-ignore
+import { readdirSync } from 'node:fs';
+
+function run() {
+  const compiledModules = Object.fromEntries(
+    readdirSync(new URL('./strings.instantiate-sync/', import.meta.url))
+      .filter((path: string) => path.endsWith('.wasm'))
+      .map((path: string) => [path, helpers.loadWasmSync(path)])
+  );
+
+  // @ts-ignore
+  const wasm = instantiateSync(compiledModules, {
+    ...helpers.wasi,
+    'test:strings/imports': {
+      takeBasic(s: string) {
+        assert.strictEqual(s, 'latin utf16');
+      },
+      returnUnicode() {
+        return '🚀🚀🚀 𠈄𓀀';
+      }
+    }
+  });
+
+  wasm.testImports();
+  assert.strictEqual(wasm.roundtrip('str'), 'str');
+  assert.strictEqual(wasm.roundtrip('🚀🚀🚀 𠈄𓀀'), '🚀🚀🚀 𠈄𓀀');
+}
+
+run()
@@ -9,6 +9,7 @@ import * as assert from 'assert';
 function run() {
   // @ts-ignore
   const wasm = instantiate(helpers.loadWasmSync, {
+    ...helpers.wasi,
     'test:strings/imports': {
       takeBasic(s: string) {
         assert.strictEqual(s, 'latin utf16');
@@ -0,0 +1,51 @@
+// Flags: --instantiation --tracing
+
+import * as helpers from './helpers.js';
+import { instantiate } from '../output/strings.tracing/strings.tracing.js';
+
+// @ts-ignore
+import * as assert from 'assert';
+
+async function run() {
+  const events: any[] = [];
+  // @ts-ignore
+  const wasm = await instantiate(helpers.loadWasm, {
+    ...helpers.wasi,
+    'test:strings/imports': {
+      takeBasic(s: string) {
+        assert.strictEqual(s, 'latin utf16');
+      },
+      returnUnicode() {
+        return '🚀🚀🚀 𠈄𓀀';
+      }
+    },
+    $trace(event: any) {
+      events.push(event);
+    }
+  });
+
+  assert.strictEqual(wasm.roundtrip('str'), 'str');
+  assert.deepStrictEqual(events, [
+    { type: 'call', module: 'roundtrip', function: 'roundtrip', args: ['str'] },
+    { type: 'return', module: 'roundtrip', function: 'roundtrip', result: 'str' },
+  ]);
+
+  events.length = 0;
+  wasm.testImports();
+  const importCalls = events
+    .filter(event => event.type === 'call')
+    .map(event => `${event.module}#${event.function}`);
+  assert.deepStrictEqual(importCalls, [
+    'test-imports#test-imports',
+    'test:strings/imports#take-basic',
+    'test:strings/imports#return-unicode',
+  ]);
+  assert.deepStrictEqual(events.find(event => event.type === 'return' && event.function === 'return-unicode'), {
+    type: 'return',
+    module: 'test:strings/imports',
+    function: 'return-unicode',
+    result: '🚀🚀🚀 𠈄𓀀',
+  });
+}
+
+await run()
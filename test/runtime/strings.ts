@@ -23,6 +23,16 @@ async function run() {
   wasm.testImports();
   assert.strictEqual(wasm.roundtrip('str'), 'str');
   assert.strictEqual(wasm.roundtrip('🚀🚀🚀 𠈄𓀀'), '🚀🚀🚀 𠈄𓀀');
+
+  // ASCII strings take the direct encoding path, the rest are re-encoded,
+  // compare both (and either side of the boundary) over many lowerings
+  const ascii = 'The quick brown fox jumps over the lazy dog. '.repeat(1000);
+  const strings = [ascii, '\x00\x7f', '\x7f\x80', 'é', ascii + 'é', 'é' + ascii, ascii + '🚀'];
+  for (let i = 0; i < 100; i++) {
+    for (const s of strings) {
+      assert.strictEqual(wasm.roundtrip(s), s);
+    }
+  }
 }
 
 await run()
@@ -0,0 +1,31 @@
+// Flags: --instantiation --wrap-sync-in-promise
+
+import * as helpers from './helpers.js';
+import { instantiate } from '../output/strings.wrap-sync-in-promise/strings.wrap-sync-in-promise.js';
+
+// @ts-ignore
+import * as assert from 'assert';
+
+async function run() {
+  // @ts-ignore
+  const wasm = await instantiate(helpers.loadWasm, {
+    ...helpers.wasi,
+    'test:strings/imports': {
+      takeBasic(s: string) {
+        assert.strictEqual(s, 'latin utf16');
+      },
+      returnUnicode() {
+        return '🚀🚀🚀 𠈄𓀀';
+      }
+    }
+  });
+
+  const result = wasm.roundtrip('str');
+  assert.ok(result instanceof Promise);
+  assert.strictEqual(await result, 'str');
+
+  await wasm.testImports();
+  assert.strictEqual(await wasm.roundtrip('🚀🚀🚀 𠈄𓀀'), '🚀🚀🚀 𠈄𓀀');
+}
+
+await run()
@@ -0,0 +1,49 @@
+// Flags: --instantiation --nullable-options
+
+import * as helpers from "./helpers.js";
+import { instantiate } from "../output/variants.nullable-options/variants.nullable-options.js";
+// @ts-ignore
+import * as assert from 'assert';
+
+async function run() {
+  // @ts-ignore
+  const wasm = await instantiate(helpers.loadWasm, {
+    ...helpers.wasi,
+    'test:variants/test': {
+      roundtripOption(x) {
+        // `none` is lifted as `null` on imports too
+        assert.notStrictEqual(x, undefined);
+        return x;
+      },
+      roundtripResult(x) {
+        if (x.tag == 'ok') {
+          return x.val;
+        } else {
+          throw Object.assign(new Error(''), { payload: Math.round(x.val) });
+        }
+      },
+      roundtripEnum(x) { return x; },
+      invertBool(x) { return !x; },
+      variantCasts(x) { return x; },
+      variantZeros(x) { return x; },
+      variantTypedefs(x, y, z) {},
+      variantEnums(a, b, c) {
+        return [
+          false,
+          { tag: 'err', val: undefined },
+          "a",
+        ];
+      }
+    }
+  });
+
+  wasm.testImports();
+  assert.strictEqual(wasm.test.roundtripOption(1), 1);
+  assert.strictEqual(wasm.test.roundtripOption(null), null);
+  // lowering still accepts `undefined`
+  assert.strictEqual(wasm.test.roundtripOption(undefined), null);
+  // @ts-ignore
+  assert.strictEqual(wasm.test.roundtripOption(), null);
+}
+
+await run()
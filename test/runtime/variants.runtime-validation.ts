@@ -0,0 +1,48 @@
+// Flags: --instantiation --runtime-validation
+
+import * as helpers from "./helpers.js";
+import { instantiate } from "../output/variants.runtime-validation/variants.runtime-validation.js";
+// @ts-ignore
+import * as assert from 'assert';
+
+async function run() {
+  // @ts-ignore
+  const wasm = await instantiate(helpers.loadWasm, {
+    ...helpers.wasi,
+    'test:variants/test': {
+      roundtripOption(x) { return x; },
+      roundtripResult(x) {
+        if (x.tag == 'ok') {
+          return x.val;
+        } else {
+          throw Object.assign(new Error(''), { payload: Math.round(x.val) });
+        }
+      },
+      roundtripEnum(x) { return x; },
+      invertBool(x) { return !x; },
+      variantCasts(x) { return x; },
+      variantZeros(x) { return x; },
+      variantTypedefs(x, y, z) {},
+      variantEnums(a, b, c) {
+        return [
+          false,
+          { tag: 'err', val: undefined },
+          "a",
+        ];
+      }
+    }
+  });
+
+  wasm.testImports();
+  assert.deepStrictEqual(wasm.test.roundtripEnum("a"), "a");
+
+  // unknown enum cases name the function they were passed to
+  assert.throws(() => wasm.test.roundtripEnum("d"), {
+    name: 'TypeError',
+    message: 'test:variants/test#roundtrip-enum: "d" is not one of the cases of e1',
+  });
+  // @ts-ignore
+  assert.throws(() => wasm.test.roundtripEnum(undefined), /roundtrip-enum: "undefined" is not one of the cases/);
+}
+
+await run()
@@ -1,5 +1,5 @@
 import { exec } from "./helpers.js";
-import { strictEqual } from "node:assert";
+import { deepStrictEqual, strictEqual, throws } from "node:assert";
 import {
   transpile,
   componentNew,
@@ -7,6 +7,7 @@ import {
 } from "../src/api.js";
 import { readFile } from "node:fs/promises";
 import { ok } from "node:assert";
+import ts from "typescript";
 
 const tscPath = "node_modules/typescript/bin/tsc";
 
@@ -73,5 +74,346 @@ export function tsTest() {
         )
       );
     });
+
+    test(`TS optional trailing params`, async () => {
+      const component = await componentNew(
+        await componentEmbed({
+          witSource: await readFile(
+            `test/fixtures/wits/optional-params/optional-params.wit`,
+            "utf8"
+          ),
+          dummy: true,
+        }),
+      );
+
+      const { files } = await transpile(component, { name: "optional-params" });
+
+      const dtsSource = new TextDecoder().decode(
+        files["interfaces/test-optional-params-funcs.d.ts"]
+      );
+
+      ok(
+        dtsSource.includes(
+          `export function trailing(a: number, b?: string | undefined, c?: number | undefined): void;`
+        )
+      );
+      // an option followed by a required param must still be passed
+      ok(
+        dtsSource.includes(
+          `export function middle(a: string | undefined, b: number): void;`
+        )
+      );
+      // options that are not represented as undefined cannot be omitted
+      ok(
+        dtsSource.includes(
+          `export function nested(a: Option<number | undefined>): void;`
+        )
+      );
+    });
+
+    test(`TS nullable options`, async () => {
+      const component = await componentNew(
+        await componentEmbed({
+          witSource: await readFile(
+            `test/fixtures/wits/optional-params/optional-params.wit`,
+            "utf8"
+          ),
+          dummy: true,
+        }),
+      );
+
+      const { files } = await transpile(component, {
+        name: "optional-params",
+        nullableOptions: true,
+      });
+
+      const dtsSource = new TextDecoder().decode(
+        files["interfaces/test-optional-params-funcs.d.ts"]
+      );
+
+      ok(
+        dtsSource.includes(
+          `export function trailing(a: number, b?: string | null, c?: number | null): void;`
+        )
+      );
+      // only the inner option of a nested option is flattened
+      ok(
+        dtsSource.includes(
+          `export function nested(a: Option<number | null>): void;`
+        )
+      );
+    });
+
+    test(`TS variant type guards`, async () => {
+      const component = await componentNew(
+        await componentEmbed({
+          witSource: await readFile(
+            `test/fixtures/wits/variant-guards/variant-guards.wit`,
+            "utf8"
+          ),
+          dummy: true,
+        }),
+      );
+
+      const { files } = await transpile(component, {
+        name: "variant-guards",
+        variantTypeGuards: true,
+      });
+
+      const dtsSource = new TextDecoder().decode(
+        files["interfaces/test-variant-guards-shapes.d.ts"]
+      );
+      ok(dtsSource.includes(`export function isShapeCircle(v: Shape): v is ShapeCircle;`));
+      // case names shared between variants get distinct guards
+      ok(dtsSource.includes(`export function isShapeNone(v: Shape): v is ShapeNone;`));
+      ok(dtsSource.includes(`export function isFillNone(v: Fill): v is FillNone;`));
+
+      const guards = await import(
+        `data:text/javascript,${encodeURIComponent(
+          new TextDecoder().decode(files["interfaces/test-variant-guards-shapes.js"])
+        )}`
+      );
+      ok(guards.isShapeCircle({ tag: "circle", val: 1 }));
+      ok(!guards.isShapeCircle({ tag: "square", val: 1 }));
+      ok(guards.isFillNone({ tag: "none" }));
+    });
+
+    test(`TS variant match helpers`, async () => {
+      const component = await componentNew(
+        await componentEmbed({
+          witSource: await readFile(
+            `test/fixtures/wits/variant-guards/variant-guards.wit`,
+            "utf8"
+          ),
+          dummy: true,
+        }),
+      );
+
+      const { files } = await transpile(component, {
+        name: "variant-guards",
+        variantMatchHelpers: true,
+      });
+
+      const dtsSource = new TextDecoder().decode(
+        files["interfaces/test-variant-guards-shapes.d.ts"]
+      );
+      ok(dtsSource.includes(
+        `export function matchShape<R>(v: Shape, handlers: { circle: (val: ShapeCircle['val']) => R, square: (val: ShapeSquare['val']) => R, none: () => R, }): R;`
+      ));
+
+      const { matchShape, matchFill } = await import(
+        `data:text/javascript,${encodeURIComponent(
+          new TextDecoder().decode(files["interfaces/test-variant-guards-shapes.js"])
+        )}`
+      );
+      const handlers = {
+        circle: (r) => Math.PI * r * r,
+        square: (s) => s * s,
+        // cases without a payload get no argument
+        none: (...args) => args.length,
+      };
+      strictEqual(matchShape({ tag: "square", val: 3 }, handlers), 9);
+      strictEqual(matchShape({ tag: "none" }, handlers), 0);
+      strictEqual(matchFill({ tag: "solid", val: "red" }, { none: () => "", solid: (c) => c }), "red");
+      throws(() => matchShape({ tag: "triangle", val: 1 }, handlers), TypeError);
+    });
+
+    test(`TS enum case arrays`, async () => {
+      const component = await componentNew(
+        await componentEmbed({
+          witSource: await readFile(
+            `test/fixtures/wits/enum-cases/enum-cases.wit`,
+            "utf8"
+          ),
+          dummy: true,
+        }),
+      );
+
+      const { files } = await transpile(component, {
+        name: "enum-cases",
+        enumCaseArrays: true,
+      });
+
+      const dtsSource = new TextDecoder().decode(
+        files["interfaces/test-enum-cases-colors.d.ts"]
+      );
+      ok(dtsSource.includes(
+        `export const ColorCases: readonly ['violet', 'blue', 'green', 'yellow-green', 'red'];`
+      ));
+      ok(dtsSource.includes(`export type Color = typeof ColorCases[number];`));
+
+      const { ColorCases } = await import(
+        `data:text/javascript,${encodeURIComponent(
+          new TextDecoder().decode(files["interfaces/test-enum-cases-colors.js"])
+        )}`
+      );
+      // cases are in WIT order
+      deepStrictEqual(ColorCases, ["violet", "blue", "green", "yellow-green", "red"]);
+      ok(Object.isFrozen(ColorCases));
+    });
+
+    test(`TS equality helpers`, async () => {
+      const component = await componentNew(
+        await componentEmbed({
+          witSource: await readFile(
+            `test/fixtures/wits/equality-helpers/equality-helpers.wit`,
+            "utf8"
+          ),
+          dummy: true,
+        }),
+      );
+
+      const { files } = await transpile(component, {
+        name: "equality-helpers",
+        equalityHelpers: true,
+      });
+
+      const dtsSource = new TextDecoder().decode(
+        files["interfaces/test-equality-helpers-values.d.ts"]
+      );
+      ok(dtsSource.includes(`export function equalsShape(a: Shape, b: Shape): boolean;`));
+      ok(dtsSource.includes(`export function equalsItems(a: Items, b: Items): boolean;`));
+
+      const { equalsShape, equalsItem, equalsItems } = await import(
+        `data:text/javascript,${encodeURIComponent(
+          new TextDecoder().decode(files["interfaces/test-equality-helpers-values.js"])
+        )}`
+      );
+
+      const shape = () => ({
+        name: "square",
+        origin: { x: 1n, y: 2n },
+        tag: undefined,
+        outline: [{ x: 0n, y: 0n }, { x: 1n, y: 1n }],
+        data: new Uint8Array([1, 2, 3]),
+      });
+      ok(equalsShape(shape(), shape()));
+      ok(!equalsShape(shape(), { ...shape(), origin: { x: 1n, y: 3n } }));
+      ok(!equalsShape(shape(), { ...shape(), tag: "tagged" }));
+      ok(!equalsShape(shape(), { ...shape(), data: new Uint8Array([1, 2, 4]) }));
+      ok(!equalsShape(shape(), { ...shape(), outline: [{ x: 0n, y: 0n }] }));
+
+      // option and result arms
+      ok(equalsItem(
+        { tag: "label", val: { tag: "err", val: "e" } },
+        { tag: "label", val: { tag: "err", val: "e" } },
+      ));
+      ok(!equalsItem(
+        { tag: "label", val: { tag: "ok", val: 1 } },
+        { tag: "label", val: { tag: "err", val: 1 } },
+      ));
+      ok(equalsItem(
+        { tag: "pair", val: [{ x: 0n, y: 0n }, { tag: "some", val: undefined }] },
+        { tag: "pair", val: [{ x: 0n, y: 0n }, { tag: "some", val: undefined }] },
+      ));
+      ok(!equalsItem(
+        { tag: "pair", val: [{ x: 0n, y: 0n }, { tag: "some", val: 1 }] },
+        { tag: "pair", val: [{ x: 0n, y: 0n }, { tag: "none" }] },
+      ));
+
+      // resource handles compare by identity
+      const handle = {};
+      ok(equalsItems(
+        [{ tag: "handle", val: handle }, { tag: "shape", val: shape() }, { tag: "empty" }],
+        [{ tag: "handle", val: handle }, { tag: "shape", val: shape() }, { tag: "empty" }],
+      ));
+      ok(!equalsItem({ tag: "handle", val: {} }, { tag: "handle", val: {} }));
+    });
+
+    test(`TS doc comment escaping`, async () => {
+      const component = await componentNew(
+        await componentEmbed({
+          witSource: await readFile(
+            `test/fixtures/wits/doc-comments/doc-comments.wit`,
+            "utf8"
+          ),
+          dummy: true,
+        }),
+      );
+
+      const { files } = await transpile(component, { name: "doc-comments" });
+
+      const dtsSource = new TextDecoder().decode(
+        files["interfaces/test-doc-comments-docs.d.ts"]
+      );
+      ok(dtsSource.includes(` * Matches paths like \`src/*\\/lib.rs\` or \`**\\/*.wit\`, where \`*\\/\` would`));
+      ok(dtsSource.includes(` * Expand a \`*\\/\`-style pattern`));
+
+      // a `*/` left in the docs would end the comment early and fail to parse
+      const { diagnostics } = ts.transpileModule(dtsSource, {
+        fileName: "test-doc-comments-docs.d.ts",
+        reportDiagnostics: true,
+      });
+      deepStrictEqual(diagnostics.map(({ messageText }) => messageText), []);
+      ok(dtsSource.includes(`export function expand(pattern: Pattern): Array<string>;`));
+    });
+
+    test(`TS branded resources`, async () => {
+      const component = await componentNew(
+        await componentEmbed({
+          witSource: await readFile(
+            `test/fixtures/wits/branded-resources/branded-resources.wit`,
+            "utf8"
+          ),
+          dummy: true,
+        }),
+      );
+
+      const { files } = await transpile(component, {
+        name: "branded",
+        brandedResources: true,
+      });
+
+      // type check against the generated declarations, which are kept in memory
+      const root = "/branded/";
+      const sources = Object.fromEntries(
+        Object.entries(files)
+          .filter(([name]) => name.endsWith(".d.ts"))
+          .map(([name, source]) => [root + name, new TextDecoder().decode(source)])
+      );
+      sources[`${root}test.ts`] = `
+import type { Handle as FooHandle } from './interfaces/test-branded-foo.js';
+import type { Handle as BarHandle } from './interfaces/test-branded-bar.js';
+declare const foo: FooHandle;
+export const sameHandle: FooHandle = foo;
+export const otherHandle: BarHandle = foo;
+`;
+      const options = {
+        noEmit: true,
+        strict: true,
+        target: ts.ScriptTarget.ES2022,
+        module: ts.ModuleKind.ESNext,
+        moduleResolution: ts.ModuleResolutionKind.Bundler,
+        types: [],
+      };
+      const host = ts.createCompilerHost(options);
+      const { directoryExists, fileExists, getSourceFile, readFile: hostReadFile } = host;
+      host.directoryExists = (dirName) =>
+        Object.keys(sources).some((name) => name.startsWith(`${dirName.replace(/\/$/, "")}/`)) ||
+        directoryExists(dirName);
+      host.fileExists = (fileName) => fileName in sources || fileExists(fileName);
+      host.readFile = (fileName) => sources[fileName] ?? hostReadFile(fileName);
+      host.getSourceFile = (fileName, languageVersion, ...args) =>
+        fileName in sources
+          ? ts.createSourceFile(fileName, sources[fileName], languageVersion)
+          : getSourceFile(fileName, languageVersion, ...args);
+      const program = ts.createProgram([`${root}test.ts`], options, host);
+
+      // the same named resources of different interfaces are not assignable
+      const diagnostics = ts.getPreEmitDiagnostics(program);
+      deepStrictEqual(
+        diagnostics.map(({ file, start, code }) => [
+          file.fileName,
+          file.getLineAndCharacterOfPosition(start).line,
+          code,
+        ]),
+        [[`${root}test.ts`, 5, 2322]]
+      );
+      ok(
+        ts
+          .flattenDiagnosticMessageText(diagnostics[0].messageText, "\n")
+          .includes(`'"test:branded/foo#handle"' is not assignable to type '"test:branded/bar#handle"'`)
+      );
+    });
   });
 }
@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
-use js_component_bindgen::BindingsMode;
-use std::{collections::HashMap, fs, io::Write, path::PathBuf};
+use js_component_bindgen::{BindingsMode, DirectorySink};
+use std::{collections::HashMap, fs, path::PathBuf};
 use wit_component::ComponentEncoder;
 use xshell::{cmd, Shell};
 
@@ -83,17 +83,25 @@ fn transpile(component_path: &str, name: String, optimize: bool) -> Result<()> {
         tracing: false,
         no_namespaced_exports: true,
         multi_memory: true,
+        branded_resources: false,
+        stability_annotations: false,
+        instantiate_sync: false,
+        errors_extend_error: false,
+        wrap_sync_in_promise: false,
+        variant_type_guards: false,
+        equality_helpers: false,
+        variant_match_helpers: false,
+        flatten_exports: false,
+        runtime_validation: false,
+        nullable_options: false,
+        abort_signals: false,
+        enum_case_arrays: false,
+        borrow_callbacks: false,
+        manifest: false,
         import_bindings: Some(BindingsMode::Js),
     };
 
-    let transpiled = js_component_bindgen::transpile(&adapted_component, opts)?;
-
-    for (filename, contents) in transpiled.files.iter() {
-        let outfile = PathBuf::from("./obj").join(filename);
-        fs::create_dir_all(outfile.parent().unwrap()).unwrap();
-        let mut file = fs::File::create(outfile).unwrap();
-        file.write_all(contents).unwrap();
-    }
+    js_component_bindgen::transpile_to(&adapted_component, opts, &mut DirectorySink::new("./obj"))?;
 
     Ok(())
 }
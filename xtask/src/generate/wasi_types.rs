@@ -37,6 +37,21 @@ pub(crate) fn run() -> Result<()> {
             tracing: false,
             no_namespaced_exports: true,
             multi_memory: false,
+            branded_resources: false,
+            stability_annotations: false,
+            instantiate_sync: false,
+            errors_extend_error: false,
+            wrap_sync_in_promise: false,
+            variant_type_guards: false,
+            equality_helpers: false,
+            variant_match_helpers: false,
+            flatten_exports: false,
+            runtime_validation: false,
+            nullable_options: false,
+            abort_signals: false,
+            enum_case_arrays: false,
+            borrow_callbacks: false,
+            manifest: false,
             import_bindings: Some(BindingsMode::Js),
         };
 